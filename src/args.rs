@@ -0,0 +1,126 @@
+//! Parsing of the command line arguments
+
+/// Options given on the command line
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    pub path: Option<String>,
+    pub help: bool,
+    pub antispoiler: bool,
+    pub inhibit_idle: bool,
+    pub beep: bool,
+    pub ask_resume: bool,
+    pub fresh: bool,
+    pub readonly: bool,
+    pub recursive: bool,
+    pub quiet: bool,
+    pub export_notes: bool,
+    pub export_bookmarks: bool,
+    pub export_chapters: bool,
+    pub socket: Option<String>,
+    pub status_file: Option<String>,
+    pub state_log: Option<String>,
+    pub bookmark_template: Option<String>,
+    pub sink: Option<String>,
+    pub sink_device: Option<String>,
+}
+
+/// Parses the arguments (without the name of the program). Unknown options and more than one path
+/// are rejected, everything after `--` is treated as a path
+pub fn parse_args(args: Vec<String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut only_paths = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            _ if only_paths => set_path(&mut parsed, arg)?,
+            "--" => only_paths = true,
+            "--help" | "-h" => parsed.help = true,
+            "--antispoiler" | "-a" => parsed.antispoiler = true,
+            "--inhibit-idle" => parsed.inhibit_idle = true,
+            "--beep" => parsed.beep = true,
+            "--ask-resume" => parsed.ask_resume = true,
+            "--fresh" => parsed.fresh = true,
+            "--readonly" => parsed.readonly = true,
+            "--recursive" | "-r" => parsed.recursive = true,
+            "--quiet" | "-q" => parsed.quiet = true,
+            "--export-notes" => parsed.export_notes = true,
+            "--export-bookmarks" => parsed.export_bookmarks = true,
+            "--export-chapters" => parsed.export_chapters = true,
+            "--socket" => parsed.socket = Some(value(&mut args, &arg, "socket path")?),
+            "--status-file" => {
+                parsed.status_file = Some(value(&mut args, &arg, "status file path")?)
+            }
+            "--state-log" => parsed.state_log = Some(value(&mut args, &arg, "state log path")?),
+            "--bookmark-name" => {
+                parsed.bookmark_template = Some(value(&mut args, &arg, "bookmark name template")?)
+            }
+            "--sink" => parsed.sink = Some(value(&mut args, &arg, "sink element")?),
+            "--sink-device" => parsed.sink_device = Some(value(&mut args, &arg, "sink device")?),
+            option if option.starts_with('-') && option.len() > 1 => {
+                return Err(format!("Unknown option {option}"));
+            }
+            _ => set_path(&mut parsed, arg)?,
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Returns the argument following the `flag`
+fn value(
+    args: &mut impl Iterator<Item = String>,
+    flag: &str,
+    what: &str,
+) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("No {what} provided after the {flag} flag"))
+}
+
+fn set_path(parsed: &mut Args, path: String) -> Result<(), String> {
+    match &parsed.path {
+        Some(previous) => Err(format!("More than one path provided: {previous} and {path}")),
+        None => {
+            parsed.path = Some(path);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|it| it.to_string()).collect())
+    }
+
+    #[test]
+    fn parse_flags_and_path() {
+        let actual = parse(&["-a", "--socket", "/tmp/gadacz", "/books/one"]).unwrap();
+        let expected = Args {
+            path: Some("/books/one".into()),
+            antispoiler: true,
+            socket: Some("/tmp/gadacz".into()),
+            ..Args::default()
+        };
+        assert_eq!(actual, expected);
+        assert!(parse(&["--help"]).unwrap().help);
+        assert!(parse(&["-q"]).unwrap().quiet);
+    }
+
+    #[test]
+    fn reject_invalid_args() {
+        assert_eq!(parse(&["-a", "-x", "/books"]), Err("Unknown option -x".into()));
+        assert!(parse(&["/books/one", "/books/two"]).is_err());
+        assert!(parse(&["--socket"]).is_err());
+    }
+
+    #[test]
+    fn paths_after_double_dash() {
+        let actual = parse(&["--", "-book", "--"]);
+        assert!(actual.is_err());
+        let actual = parse(&["-a", "--", "-book"]).unwrap();
+        assert_eq!(actual.path.as_deref(), Some("-book"));
+        assert!(actual.antispoiler);
+    }
+}
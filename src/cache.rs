@@ -2,6 +2,7 @@ use tui::widgets::ListItem;
 
 use crate::data::chapter::{formatted_time,
                            Chapter};
+use crate::helpers::format_countdown;
 
 pub struct Cache<'a> {
     // should_recalculate: bool,
@@ -12,6 +13,8 @@ pub struct Cache<'a> {
     pub pl_lengths: Option<Vec<ListItem<'a>>>,
     pub pl_percentages: Option<Vec<ListItem<'a>>>,
     pub pl_titles: Option<Vec<ListItem<'a>>>,
+    pub pl_frontier: Option<Option<usize>>, // index of the furthest chapter with any progress
+    pub book_progress: Option<String>,
 
     pub abs_now: Option<String>,
     pub formatted_abs_now: Option<String>,
@@ -20,6 +23,9 @@ pub struct Cache<'a> {
 
     pub bk_list0: Option<Vec<ListItem<'a>>>,
     pub bk_list1: Option<Vec<ListItem<'a>>>,
+    pub bks_total: Option<String>,
+
+    pub sleep_countdown: Option<(u64, String)>, // remaining seconds of the sleep timer, formatted
 
     pub keybidings_list0: Option<Vec<ListItem<'a>>>,
     pub keybidings_list1: Option<Vec<ListItem<'a>>>,
@@ -32,6 +38,9 @@ impl Cache<'_> {
                 ListItem::new("Chapter: "),
                 ListItem::new("Book: "),
                 ListItem::new("Author: "),
+                ListItem::new("Narrator: "),
+                ListItem::new("Series: "),
+                ListItem::new("Bookmarks: "),
                 ListItem::new("File name: "),
                 ListItem::new("Dir: "),
                 ListItem::new("Speed: "),
@@ -44,6 +53,8 @@ impl Cache<'_> {
             pl_lengths: None,
             pl_percentages: None,
             pl_titles: None,
+            pl_frontier: None,
+            book_progress: None,
 
             abs_now: None,
             formatted_abs_now: None,
@@ -52,6 +63,9 @@ impl Cache<'_> {
 
             bk_list0: None,
             bk_list1: None,
+            bks_total: None,
+
+            sleep_countdown: None,
 
             keybidings_list0: None,
             keybidings_list1: None,
@@ -68,10 +82,22 @@ impl Cache<'_> {
         self.abs_now = Some(abs_position.to_string());
     }
 
+    /// Formats the remaining seconds of the sleep timer only when they change, `None` clears it
+    pub fn update_sleep_countdown(&mut self, remaining: Option<u64>) {
+        match remaining {
+            Some(secs) if self.sleep_countdown.as_ref().map(|it| it.0) != Some(secs) => {
+                self.sleep_countdown = Some((secs, format_countdown(secs)));
+            }
+            Some(_) => {}
+            None => self.sleep_countdown = None,
+        }
+    }
+
     /// invalidates the cache for things related to bookmarks
     pub fn invalidate_bks(&mut self) {
         self.bk_list0 = None;
         self.bk_list1 = None;
+        self.bks_total = None;
         self.pl_bks_count = None;
     }
 
@@ -81,6 +107,14 @@ impl Cache<'_> {
         self.keybidings_list1 = None;
     }
 
+    /// invalidates the things in the playlist depending on the positions of the chapters
+    pub fn invalidate_progress(&mut self) {
+        self.pl_percentages = None;
+        self.pl_frontier = None;
+        self.pl_titles = None; // the frontier is styled in the titles
+        self.book_progress = None;
+    }
+
     /// invalidates the things in the playlist
     pub fn invalidate_pls(&mut self) {
         self.pl_bks_count = None;
@@ -88,13 +122,18 @@ impl Cache<'_> {
         self.pl_lengths = None;
         self.pl_percentages = None;
         self.pl_titles = None;
+        self.pl_frontier = None;
+        self.book_progress = None;
     }
 
-    pub fn invalide_all(&mut self) {
-        self.formmated_now = None;
+    /// invalidates everything that is computed from the current chapter
+    pub fn invalidate_all(&mut self) {
+        self.abs_now = None;
         self.formatted_abs_now = None;
         self.formatted_length = None;
+        self.formmated_now = None;
         self.invalidate_bks();
+        self.invalidate_kbs();
         self.invalidate_pls();
     }
 }
@@ -104,3 +143,85 @@ impl Default for Cache<'_> {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled<'a>() -> Cache<'a> {
+        let item = || Some(vec![ListItem::new("x")]);
+        let string = || Some("x".to_string());
+        Cache {
+            info_info: vec![ListItem::new("x")],
+            pl_bks_count: item(),
+            pl_chooses: item(),
+            pl_lengths: item(),
+            pl_percentages: item(),
+            pl_titles: item(),
+            pl_frontier: Some(Some(0)),
+            book_progress: string(),
+            abs_now: string(),
+            formatted_abs_now: string(),
+            formatted_length: string(),
+            formmated_now: string(),
+            bk_list0: item(),
+            bk_list1: item(),
+            bks_total: string(),
+            sleep_countdown: Some((1, "x".to_string())),
+            keybidings_list0: item(),
+            keybidings_list1: item(),
+        }
+    }
+
+    #[test]
+    fn invalidate_all_resets_every_field() {
+        let mut cache = filled();
+        cache.invalidate_all();
+
+        // destructuring without `..` makes this test fail to compile when a new field is added
+        let Cache {
+            info_info,
+            pl_bks_count,
+            pl_chooses,
+            pl_lengths,
+            pl_percentages,
+            pl_titles,
+            pl_frontier,
+            book_progress,
+            abs_now,
+            formatted_abs_now,
+            formatted_length,
+            formmated_now,
+            bk_list0,
+            bk_list1,
+            bks_total,
+            sleep_countdown,
+            keybidings_list0,
+            keybidings_list1,
+        } = cache;
+
+        assert!(!info_info.is_empty()); // static, never invalidated
+        assert!(sleep_countdown.is_some()); // follows the sleep timer instead of the chapter
+        assert!(pl_frontier.is_none());
+
+        for it in [
+            pl_bks_count,
+            pl_chooses,
+            pl_lengths,
+            pl_percentages,
+            pl_titles,
+            bk_list0,
+            bk_list1,
+            keybidings_list0,
+            keybidings_list1,
+        ] {
+            assert!(it.is_none());
+        }
+
+        for it in
+            [abs_now, formatted_abs_now, formatted_length, formmated_now, bks_total, book_progress]
+        {
+            assert!(it.is_none());
+        }
+    }
+}
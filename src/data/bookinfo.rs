@@ -0,0 +1,125 @@
+//! Book-level information read from sidecar files inside the book directory
+//!
+//! `metadata.json` is read first and has the following schema, where every field is optional:
+//!
+//! ```json
+//! {
+//!     "title": "The Book",
+//!     "author": "Some Author",
+//!     "narrator": "Some Narrator",
+//!     "series": "Some Series #1"
+//! }
+//! ```
+//!
+//! If there is no `metadata.json`, the first `.nfo` file is scanned for `Title:`, `Author:`,
+//! `Narrator:` and `Series:` lines (case insensitive).
+//!
+//! Cover art is looked up as `cover`, `folder` or `front` with a `.jpg`, `.jpeg` or `.png`
+//! extension (case insensitive), in that order.
+
+use std::path::{Path,
+                PathBuf};
+
+use serde::Deserialize;
+
+const COVER_NAMES: [&str; 3] = ["cover", "folder", "front"];
+const COVER_EXTENSIONS: [&str; 3] = ["jpg", "jpeg", "png"];
+
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct BookInfo {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub narrator: Option<String>,
+    #[serde(default)]
+    pub series: Option<String>,
+}
+
+impl BookInfo {
+    /// Reads the book information from `metadata.json` or a `.nfo` file under the `path`
+    pub fn from_dir(path: &Path) -> Option<Self> {
+        if let Ok(data) = std::fs::read_to_string(path.join("metadata.json")) {
+            return serde_json::from_str(&data).ok();
+        }
+
+        let nfo = path.read_dir().ok()?.filter_map(Result::ok).find(|it| {
+            it.path().extension().map(|ext| ext.eq_ignore_ascii_case("nfo")).unwrap_or(false)
+        })?;
+        let data = std::fs::read(nfo.path()).ok()?;
+        Some(Self::from_nfo(&String::from_utf8_lossy(&data)))
+    }
+
+    /// Parses `Key: value` lines of a `.nfo` file
+    pub fn from_nfo(data: &str) -> Self {
+        let mut info = Self::default();
+
+        for line in data.lines() {
+            let (key, value) = match line.split_once(':') {
+                Some(it) => it,
+                None => continue,
+            };
+
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+
+            let field = match key.trim().to_lowercase().as_str() {
+                "title" => &mut info.title,
+                "author" => &mut info.author,
+                "narrator" | "read by" => &mut info.narrator,
+                "series" => &mut info.series,
+                _ => continue,
+            };
+
+            if field.is_none() {
+                *field = Some(value.to_owned());
+            }
+        }
+
+        info
+    }
+}
+
+/// Returns the path of the cover art image under the `path`
+pub fn find_cover(path: &Path) -> Option<PathBuf> {
+    let files: Vec<_> = path.read_dir().ok()?.filter_map(Result::ok).map(|it| it.path()).collect();
+    COVER_NAMES.iter().find_map(|name| files.iter().find(|it| is_cover(it, name)).cloned())
+}
+
+/// Returns whether the `file` is an image called `name`
+fn is_cover(file: &Path, name: &str) -> bool {
+    file.file_stem().is_some_and(|it| it.eq_ignore_ascii_case(name))
+        && file
+            .extension()
+            .is_some_and(|ext| COVER_EXTENSIONS.iter().any(|it| ext.eq_ignore_ascii_case(it)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nfo_fields() {
+        let data = "General Information\n===\nTitle:      The Book\nAUTHOR: Some Author\nRead By: \
+                    Some Narrator\nSeries:\nGenre: Fiction";
+        let actual = BookInfo::from_nfo(data);
+        let expected = BookInfo {
+            title: Some("The Book".to_string()),
+            author: Some("Some Author".to_string()),
+            narrator: Some("Some Narrator".to_string()),
+            series: None,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn cover_names() {
+        assert!(is_cover(Path::new("/books/one/Cover.JPG"), "cover"));
+        assert!(is_cover(Path::new("/books/one/folder.png"), "folder"));
+        assert!(!is_cover(Path::new("/books/one/cover.txt"), "cover"));
+        assert!(!is_cover(Path::new("/books/one/back-cover.jpg"), "cover"));
+    }
+}
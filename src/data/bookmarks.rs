@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use serde::{Deserialize,
             Serialize};
 
@@ -9,12 +11,28 @@ pub struct Bookmark {
     pub name: String,
     /// user given name for the bookmark
     pub formatted_position: String,
+    /// unix timestamp of when the bookmark was added on pause, `None` if the user added it
+    #[serde(default)]
+    pub paused_at: Option<u64>,
 }
 
 impl Bookmark {
+    /// Returns the formatted position, with `relative_only` the position in the file is left out
+    /// and only the one relative to the start of the chapter at `start_position` is shown
+    pub fn display(&self, start_position: Option<u64>, relative_only: bool) -> Cow<'_, str> {
+        match start_position {
+            Some(start_position) if relative_only => Cow::Owned(format_position(
+                &self.name,
+                self.position.saturating_sub(start_position),
+                None,
+            )),
+            _ => Cow::Borrowed(&self.formatted_position),
+        }
+    }
+
     pub fn new(position: u64, start_position: Option<u64>, name: String) -> Self {
         let formatted_position = format_position(&name, position, start_position);
-        Self { position, name, formatted_position }
+        Self { position, name, formatted_position, paused_at: None }
     }
 
     pub fn change_name(&mut self, new_name: String) {
@@ -100,6 +118,13 @@ fn format_position(name: &String, position: u64, start_position: Option<u64>) ->
 mod tests {
     use super::*;
 
+    #[test]
+    fn relative_display() {
+        let data = Bookmark::new(3661, Some(3600), "bk".to_string());
+        assert_eq!(data.display(Some(3600), true), "\"bk\" at 1m1s");
+        assert_eq!(data.display(Some(3600), false), "\"bk\" at 1m1s(1h1m1s)");
+    }
+
     #[test]
     fn formatted_display0() {
         let data = Bookmark::new(1, None, "bk".to_string());
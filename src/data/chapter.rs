@@ -5,7 +5,8 @@ use serde::{Deserialize,
             Serialize};
 
 use super::bookmarks::Bookmark;
-use super::make_uri;
+use super::{make_uri,
+            DISCOVER_TIMEOUT};
 
 macro_rules! get {
     ($tag:expr, $ty:ty) => {
@@ -34,6 +35,14 @@ pub struct Chapter {
     #[serde(default)]
     pub description: Option<String>,
 
+    #[serde(default)]
+    pub last_played_at: Option<u64>, // unix timestamp of the last position update
+
+    #[serde(default)]
+    pub speed_override: Option<f64>, // used instead of the speed of the book
+    #[serde(default)]
+    pub volume_override: Option<f64>, // used instead of the volume of the book
+
     #[serde(skip)]
     pub title: Option<String>,
     #[serde(skip)]
@@ -69,20 +78,23 @@ impl Chapter {
         start_position: Option<u64>,
         length: Option<u64>,
     ) -> Self {
-        let filename = file_path.file_name().unwrap().to_str().unwrap().to_owned();
+        let filename = super::relative_filename(file_path, path);
         let mut path = path.to_path_buf();
         path.push(&filename);
 
-        let length = if let Some(length) = length {
-            length
+        // if the discovery fails or times out the length stays unknown (0) until the chapter is
+        // played
+        let length = length.or_else(|| {
+            let disc = gstreamer_pbutils::Discoverer::new(DISCOVER_TIMEOUT).ok()?;
+            disc.discover_uri(&make_uri(&path)).ok()?.duration().map(gst::ClockTime::seconds)
+        });
+
+        let (length, length_display) = if let Some(length) = length {
+            (length, formatted_time(length))
         } else {
-            let disc = gstreamer_pbutils::Discoverer::new(gst::ClockTime::from_seconds(1)).unwrap();
-            let disc = disc.discover_uri(&make_uri(&path)).unwrap();
-            disc.duration().unwrap().seconds()
+            (0, String::new())
         };
 
-        let length_display = formatted_time(length);
-
         Self {
             filename,
             last_position: 0,
@@ -92,6 +104,9 @@ impl Chapter {
             length_display,
 
             description: None,
+            last_played_at: None,
+            speed_override: None,
+            volume_override: None,
 
             m4_title: None,
             m4_tracknumber: None,
@@ -130,6 +145,9 @@ impl Chapter {
             length_display,
 
             description: None,
+            last_played_at: None,
+            speed_override: None,
+            volume_override: None,
 
             m4_title: title,
             m4_tracknumber: subtracknumber,
@@ -145,20 +163,39 @@ impl Chapter {
         }
     }
 
-    /// Gets information about the chapter from tags
+    /// Gets information about the chapter from tags. Returns `false` if the file couldn't be
+    /// discovered in time
     // path is the path to the dir from the user
-    pub fn get_info_from_tags(&mut self, path: &Path, disc: &gstreamer_pbutils::Discoverer) {
+    pub fn get_info_from_tags(
+        &mut self,
+        path: &Path,
+        disc: &gstreamer_pbutils::Discoverer,
+    ) -> bool {
         let mut path = path.to_path_buf();
         path.push(&self.filename);
-        let disc = disc.discover_uri(&make_uri(&path)).unwrap();
-        let tags = disc.tags().unwrap();
+        let tags = match disc.discover_uri(&make_uri(&path)) {
+            Ok(info) => info.tags(),
+            Err(_) => return false,
+        };
+        let tags = match tags {
+            Some(tags) => tags,
+            None => return true,
+        };
 
-        self.title = get!(tags, gst::tags::Title);
+        // the title is read again on every start, so it's trimmed here instead of with 'T'
+        self.title = get!(tags, gst::tags::Title).map(|it| it.trim().to_owned());
         self.album = get!(tags, gst::tags::Album);
         self.artist = get!(tags, gst::tags::Artist);
         self.desc_from_file = get!(tags, gst::tags::Description);
         self.trackcount = get!(tags, gst::tags::TrackCount);
         self.tracknumber = get!(tags, gst::tags::TrackNumber);
+        true
+    }
+
+    /// Sets the length of the chapter, used when it wasn't known when the chapter was created
+    pub fn set_length(&mut self, length: u64) {
+        self.length = length;
+        self.length_display = formatted_time(length);
     }
 
     pub fn formatted_length(&self) -> String {
@@ -185,6 +222,33 @@ impl Chapter {
         self.bookmarks.push(Bookmark::new(position, self.start_position, name));
     }
 
+    /// Adds a bookmark where the playback was paused, keeping at most `keep` of such bookmarks by
+    /// removing the oldest ones. Pausing again at the same position doesn't add another one
+    pub fn add_pause_bookmark(&mut self, position: u64, keep: usize) {
+        if self.bookmarks.iter().any(|it| it.paused_at.is_some() && it.position == position) {
+            return;
+        }
+
+        let mut bookmark = Bookmark::new(position, self.start_position, "Paused".to_string());
+        bookmark.paused_at = Some(crate::helpers::unix_now());
+        self.bookmarks.push(bookmark);
+
+        while self.bookmarks.iter().filter(|it| it.paused_at.is_some()).count() > keep {
+            // the earlier one wins a tie, bookmarks are only appended
+            let oldest = self
+                .bookmarks
+                .iter()
+                .enumerate()
+                .filter_map(|(index, it)| Some((index, it.paused_at?)))
+                .min_by_key(|&(_, paused_at)| paused_at)
+                .map(|(index, _)| index);
+            match oldest {
+                Some(index) => self.bookmarks.remove(index),
+                None => break,
+            };
+        }
+    }
+
     pub fn get_title_or_filename(&self) -> &String {
         if let Some(m4_title) = self.m4_title.as_ref() {
             return m4_title;
@@ -201,6 +265,7 @@ impl Chapter {
 
     pub fn update_last_position(&mut self, position: u64) {
         self.last_position = position;
+        self.last_played_at = Some(crate::helpers::unix_now());
     }
 
     pub fn update_saved_position(&mut self, position: Option<u64>) {
@@ -211,6 +276,45 @@ impl Chapter {
         self.start_position.unwrap_or(0)
     }
 
+    /// Clamps the absolute `position` into the part of the file belonging to the chapter, so
+    /// seeking inside a m4a/m4b file doesn't spill into its other chapters
+    pub fn clamp_position(&self, position: u64) -> u64 {
+        let start = self.get_start_position();
+        if self.length == 0 {
+            // the length is not known yet
+            return position.max(start);
+        }
+        position.clamp(start, start + self.length)
+    }
+
+    /// Sets the last position to the end of the chapter. The end is clamped to `file_duration` (in
+    /// seconds) so a length of a m4a/m4b chapter computed from the duration of the whole file can't
+    /// point past its end, the length gets corrected if it did
+    pub fn mark_complete(&mut self, file_duration: Option<u64>) {
+        let start = self.get_start_position();
+        let end = start + self.length;
+        match file_duration {
+            Some(duration) if duration > start && duration < end => {
+                self.set_length(duration - start);
+                self.update_last_position(duration);
+            }
+            _ => self.update_last_position(end),
+        }
+    }
+
+    /// Returns how much of the chapter was listened to, from 0.0 to 1.0
+    pub fn completion(&self) -> f64 {
+        if self.length == 0 {
+            return 0.0;
+        }
+        self.listened() as f64 / self.length as f64
+    }
+
+    /// Returns how many seconds of the chapter were listened to, at most its length
+    pub fn listened(&self) -> u64 {
+        self.last_position.saturating_sub(self.get_start_position()).min(self.length)
+    }
+
     pub fn delete_bookmark(&mut self, index: usize) -> Bookmark {
         self.bookmarks.swap_remove(index)
     }
@@ -239,3 +343,65 @@ pub fn formatted_time(length: u64) -> String {
 
     format!("{}h{}m{}s", hours, minutes, seconds)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_is_clamped_to_the_chapter() {
+        let chapter = Chapter::from_m4("book.m4b".to_string(), None, Some(100), Some(1), Some(50));
+        assert_eq!(chapter.clamp_position(20), 100);
+        assert_eq!(chapter.clamp_position(120), 120);
+        assert_eq!(chapter.clamp_position(200), 150);
+    }
+
+    #[test]
+    fn last_m4b_chapter_is_completed_at_the_file_end() {
+        // chapters of a 361s long file, the rounded up start of the last one makes its length
+        // overshoot the end of the file by 1s
+        let chapter = |index: u32| {
+            let (start, length) = [(0, 100), (100, 200), (300, 62)][index as usize];
+            Chapter::from_m4("book.m4b".to_string(), None, Some(start), Some(index), Some(length))
+        };
+
+        let mut last = chapter(2);
+        last.mark_complete(Some(361));
+        assert_eq!(last.last_position, 361);
+        assert_eq!(last.length, 61);
+        assert_eq!(last.completion(), 1.0);
+
+        let mut middle = chapter(1);
+        middle.mark_complete(Some(361));
+        assert_eq!(middle.last_position, 300);
+
+        let mut unknown = chapter(2);
+        unknown.mark_complete(None);
+        assert_eq!(unknown.last_position, 362);
+    }
+
+    #[test]
+    fn oldest_pause_bookmarks_are_pruned() {
+        let mut chapter =
+            Chapter::from_m4("book.m4b".to_string(), None, Some(100), Some(1), Some(50));
+        chapter.add_bookmark("mine".to_string(), 105);
+        for position in [110, 120, 120, 130] {
+            chapter.add_pause_bookmark(position, 2);
+        }
+
+        let positions: Vec<_> = chapter.bookmarks.iter().map(|it| it.position).collect();
+        assert_eq!(positions, vec![105, 120, 130]);
+        assert!(chapter.bookmarks[0].paused_at.is_none());
+    }
+
+    #[test]
+    fn listened_is_relative_to_the_chapter() {
+        let mut chapter =
+            Chapter::from_m4("book.m4b".to_string(), None, Some(100), Some(1), Some(50));
+        assert_eq!(chapter.listened(), 0);
+        chapter.update_last_position(120);
+        assert_eq!(chapter.listened(), 20);
+        chapter.update_last_position(400);
+        assert_eq!(chapter.listened(), 50);
+    }
+}
@@ -0,0 +1,282 @@
+//! Global configuration stored in `$XDG_CONFIG_HOME/gadacz/config.json`, falling back to
+//! `~/.config/gadacz/config.json`
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize,
+            Serialize};
+
+type EyreResult<T> = color_eyre::Result<T>;
+
+/// Settings shared by all books
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Config {
+    #[serde(default = "default_speed")]
+    pub speed: f64, // speed of books played for the first time
+    #[serde(default = "default_volume")]
+    pub volume: f64, // volume of books played for the first time
+    #[serde(default = "default_volume_step")]
+    pub volume_step: f64, // how much + and - change the volume
+    #[serde(default = "default_big_jump_secs")]
+    pub big_jump_secs: u64, // how far H and L move
+    #[serde(default = "default_auto_advance")]
+    pub auto_advance: bool, // if true the next chapter starts when the current one ends
+    #[serde(default)]
+    pub confirm: Confirm, // which destructive actions ask for a confirmation
+    #[serde(default = "default_render_ms")]
+    pub render_ms: u64, // milliseconds between redraws of the ui
+    #[serde(default = "default_update_ms")]
+    pub update_ms: u64, // milliseconds between queries of the position and other state updates
+    #[serde(default = "default_message_secs")]
+    pub message_secs: u64, // seconds a message is shown, errors stay until a key is pressed
+    #[serde(default)]
+    pub wrap_around: bool, // if true moving past the last chapter goes to the first and back
+    #[serde(default)]
+    pub loop_book: bool, // if true the first chapter starts when the last one ends
+    #[serde(default)]
+    pub keys: HashMap<String, String>, // keys remapped onto the built-in ones, see `keymap`
+    #[serde(default)]
+    pub end_of_book_grace_secs: u64, /* seconds of silence played after the book ends before
+                                      * pausing */
+    #[serde(default = "default_speed_presets")]
+    pub speed_presets: Vec<f64>, // speeds x cycles through
+    #[serde(default)]
+    pub seek_past_end: SeekPastEnd, // what moving forwards past the end of a chapter does
+    #[serde(default)]
+    pub pause_bookmarks: usize, // bookmarks added on pause kept per chapter, 0 doesn't add them
+    #[serde(default)]
+    pub relative_positions: bool, // if true m4a/m4b positions are shown relative to the chapter
+    #[serde(default = "default_progress_colors")]
+    pub progress_colors: Vec<ProgressColor>, // colors of the percentages in the playlist
+    #[serde(default)]
+    pub sink: Option<String>, // sink used instead of autoaudiosink, `--sink` overrides it
+    #[serde(default)]
+    pub sink_device: Option<String>, // device property of the sink, `--sink-device` overrides it
+}
+
+/// Maps names of destructive actions (`delete_bookmark`, `delete_description`) to whether they ask
+/// for a confirmation
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Confirm(HashMap<String, bool>);
+
+impl Confirm {
+    /// Returns whether the `action` asks for a confirmation, `default` if the config doesn't say
+    pub fn get(&self, action: &str, default: bool) -> bool {
+        self.0.get(action).copied().unwrap_or(default)
+    }
+}
+
+/// Color of the percentages in the playlist starting `from` that many percent, the color is parsed
+/// by `ui::colors`
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ProgressColor {
+    pub from: u16,
+    pub color: String,
+}
+
+/// What happens when moving forwards goes past the end of the chapter
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SeekPastEnd {
+    /// The position stays at the end of the chapter
+    #[default]
+    Stop,
+    /// The next chapter is loaded
+    Advance,
+}
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+fn default_volume() -> f64 {
+    0.5
+}
+
+fn default_volume_step() -> f64 {
+    0.05
+}
+
+fn default_big_jump_secs() -> u64 {
+    300
+}
+
+fn default_auto_advance() -> bool {
+    true
+}
+
+fn default_render_ms() -> u64 {
+    33
+}
+
+fn default_update_ms() -> u64 {
+    200
+}
+
+fn default_message_secs() -> u64 {
+    4
+}
+
+fn default_speed_presets() -> Vec<f64> {
+    vec![1.0, 1.25, 1.5, 1.75, 2.0]
+}
+
+fn default_progress_colors() -> Vec<ProgressColor> {
+    [(75, "green"), (50, "light_green"), (25, "gray"), (0, "dark_gray")]
+        .into_iter()
+        .map(|(from, color)| ProgressColor { from, color: color.to_string() })
+        .collect()
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            speed: default_speed(),
+            volume: default_volume(),
+            volume_step: default_volume_step(),
+            big_jump_secs: default_big_jump_secs(),
+            auto_advance: default_auto_advance(),
+            confirm: Confirm::default(),
+            render_ms: default_render_ms(),
+            update_ms: default_update_ms(),
+            message_secs: default_message_secs(),
+            wrap_around: false,
+            loop_book: false,
+            keys: HashMap::new(),
+            end_of_book_grace_secs: 0,
+            speed_presets: default_speed_presets(),
+            seek_past_end: SeekPastEnd::Stop,
+            pause_bookmarks: 0,
+            relative_positions: false,
+            progress_colors: default_progress_colors(),
+            sink: None,
+            sink_device: None,
+        }
+    }
+}
+
+impl Config {
+    /// Returns the path of the config file, `None` if neither `$XDG_CONFIG_HOME` nor `$HOME` is
+    /// set
+    pub fn path() -> Option<PathBuf> {
+        let dir = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(dir.join("gadacz").join("config.json"))
+    }
+
+    /// Reads the config file, if it doesn't exist returns the default config. Fails if the file
+    /// exists but can't be read or parsed
+    pub fn load() -> EyreResult<Self> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+
+        let data = match std::fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => eyre::bail!("Couldn't read {}: {err}", path.display()),
+        };
+
+        match serde_json::from_str::<Self>(&data) {
+            Ok(config) => Ok(config.sanitized()),
+            Err(err) => eyre::bail!("Couldn't parse {}: {err}", path.display()),
+        }
+    }
+
+    /// Replaces a speed, volume or volume step the player can't use with the default and drops such
+    /// speed presets
+    fn sanitized(mut self) -> Self {
+        if self.speed <= 0.0 {
+            self.speed = default_speed();
+        }
+        self.speed_presets.retain(|&it| it > 0.0);
+        if !(0.0..=1.0).contains(&self.volume) {
+            self.volume = default_volume();
+        }
+        if !(self.volume_step > 0.0 && self.volume_step <= 1.0) {
+            self.volume_step = default_volume_step();
+        }
+        self
+    }
+
+    /// Writes the config file, creating its directory if needed
+    pub fn save(&self) -> EyreResult<()> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => eyre::bail!(
+                "Couldn't find the config directory, neither $XDG_CONFIG_HOME nor $HOME is set"
+            ),
+        };
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(&self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_are_defaulted() {
+        let actual: Config = serde_json::from_str(r#"{"speed": 1.5}"#).unwrap();
+        let expected = Config {
+            speed: 1.5,
+            volume: 0.5,
+            volume_step: 0.05,
+            big_jump_secs: 300,
+            auto_advance: true,
+            confirm: Confirm::default(),
+            render_ms: 33,
+            update_ms: 200,
+            message_secs: 4,
+            wrap_around: false,
+            loop_book: false,
+            keys: HashMap::new(),
+            end_of_book_grace_secs: 0,
+            speed_presets: vec![1.0, 1.25, 1.5, 1.75, 2.0],
+            seek_past_end: SeekPastEnd::Stop,
+            pause_bookmarks: 0,
+            relative_positions: false,
+            progress_colors: default_progress_colors(),
+            sink: None,
+            sink_device: None,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn invalid_speed_and_volume_are_defaulted() {
+        let json =
+            r#"{"speed": 0.0, "volume": 3.0, "volume_step": 0.0, "speed_presets": [1.0, -1.0]}"#;
+        let actual = serde_json::from_str::<Config>(json).unwrap().sanitized();
+        assert_eq!(actual.speed, 1.0);
+        assert_eq!(actual.volume, 0.5);
+        assert_eq!(actual.volume_step, 0.05);
+        assert_eq!(actual.speed_presets, vec![1.0]);
+    }
+
+    #[test]
+    fn seek_past_end_is_snake_case() {
+        let actual: Config = serde_json::from_str(r#"{"seek_past_end": "advance"}"#).unwrap();
+        assert_eq!(actual.seek_past_end, SeekPastEnd::Advance);
+        assert!(serde_json::from_str::<Config>(r#"{"seek_past_end": "Advance"}"#).is_err());
+    }
+
+    #[test]
+    fn confirm_falls_back_to_default() {
+        let actual: Config =
+            serde_json::from_str(r#"{"confirm": {"delete_bookmark": false}}"#).unwrap();
+        assert!(!actual.confirm.get("delete_bookmark", true));
+        assert!(actual.confirm.get("delete_description", true));
+        assert!(!actual.confirm.get("delete_description", false));
+    }
+}
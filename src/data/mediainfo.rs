@@ -3,20 +3,27 @@ use std::path::{Path,
 
 use color_eyre::{Help,
                  Report};
-use gstreamer as gst;
 use serde::{Deserialize,
             Serialize};
 
+use super::bookinfo::{self,
+                      BookInfo};
 use super::chapter::Chapter;
+use super::config::Config;
+use super::{notes,
+            DISCOVER_TIMEOUT};
+use crate::helpers;
 
 const M4_EXTENSIONS: [&str; 2] = ["m4a", "m4b"];
-const VALID_EXTENSIONS: [&str; 8] = ["flac", "m4a", "m4b", "mp3", "mp4", "ogg", "opus", "wav"];
+const VALID_EXTENSIONS: [&str; 12] =
+    ["aac", "aif", "aiff", "flac", "m4a", "m4b", "mp3", "mp4", "ogg", "opus", "wav", "wma"];
+// files with these extensions are only played if the installed gstreamer plugins can decode them
+const CHECKED_EXTENSIONS: [&str; 4] = ["aac", "aif", "aiff", "wma"];
 
 type EyreResult<T> = color_eyre::Result<T>;
 
 #[derive(Debug)]
 enum Error {
-    NoExtentsion(PathBuf),
     FromIo(std::io::Error),
     MissingFiles(Vec<PathBuf>),
 }
@@ -24,7 +31,6 @@ enum Error {
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::NoExtentsion(s) => write!(f, "{s:#?} has no extension"),
             Error::FromIo(io) => io.fmt(f),
             Error::MissingFiles(v) => write!(
                 f,
@@ -47,43 +53,122 @@ impl std::error::Error for Error {}
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MediaInfo {
     #[serde(skip)]
-    pub chaptercount: usize,
+    pub path: PathBuf, // path for the currently playing book
 
     #[serde(skip)]
-    pub path: PathBuf, // path for the currently playing book
+    pub book_info: Option<BookInfo>, // information from `metadata.json` or a `.nfo` file
+
+    #[serde(skip)]
+    pub cover: Option<PathBuf>, // cover art image found in the directory, see `bookinfo`
+
+    #[serde(skip)]
+    pub new_files: Vec<String>, // files found in the directory since the last start
+
+    #[serde(skip)]
+    pub scan_errors: Vec<String>, // files that couldn't be read properly during the scan
+
+    #[serde(skip)]
+    pub scan_timeouts: usize, // files whose tags couldn't be read in time during the scan
+
+    #[serde(skip)]
+    pub is_readonly: bool, // if true `gadacz_data.json` is never written, set with `--readonly`
 
     #[serde(default)]
     pub is_antispoiler: bool, // if true antispoiler mode is active
 
+    #[serde(default)]
+    pub is_fading: bool, // if true volume fades in and out on play and pause
+
+    #[serde(default)]
+    pub is_reverse_advance: bool, // if true moving backwards past the start goes to prev chapter
+
+    #[serde(default)]
+    pub intro_skip_secs: u64, // skipped at the start of every chapter that wasn't played yet
+
+    #[serde(default)]
+    pub marked_position: Option<u64>, // position in the last chapter marked with 'm'
+
+    #[serde(default = "default_seek_step")]
+    pub seek_step: u64, // seconds moved by h and l
+
+    #[serde(default)]
+    pub is_recursive: bool, // if true files in subdirectories are part of the book, `--recursive`
+
+    #[serde(default)]
+    pub is_custom_order: bool, // if true chapters were reordered by the user and aren't sorted
+
+    #[serde(default)]
+    pub book_note: Option<String>, // free-form note for the whole book, unlike the descriptions
+
+    #[serde(default)]
+    pub is_finished: bool, // if true the book was listened to the end or marked as finished
+
+    #[serde(default)]
+    pub removed_files: Vec<String>, // files removed from the playlist by the user
+
     pub speed: f64,
     pub volume: f64,
     pub last_chapter: usize,    // index of the last played chapter
     pub chapters: Vec<Chapter>, // list of chapters for the given book
 }
 
+fn default_seek_step() -> u64 {
+    5
+}
+
 impl MediaInfo {
     /// Read cached `MediaInfo` from ``gadacz_data.json`` under the given path, if it doesn't exist
-    /// scan the `path` and create new ``MediaInfo``
-    pub fn from_cache_or_new(path: &Path) -> EyreResult<Self> {
-        if let Some(file) =
+    /// scan the `path` and create new ``MediaInfo`` with the defaults from the `config`. The
+    /// `progress` of scanning is reported as in `scan_chapters`. With `recursive` the files in
+    /// subdirectories are scanned too, a book once scanned that way always is
+    pub fn from_cache_or_new(
+        path: &Path,
+        config: &Config,
+        recursive: bool,
+        progress: &mut dyn FnMut(&str, usize, usize),
+    ) -> EyreResult<Self> {
+        let mut mi = if let Some(file) =
             path.read_dir()?.find(|x| x.as_ref().unwrap().file_name() == "gadacz_data.json")
         {
-            let mut mi = MediaInfo::from_json(path, file?)?;
+            let mut mi = MediaInfo::from_json(path, file?, recursive, progress)?;
             mi.path = path.to_owned(); // path is not being cached
-            Ok(mi)
+            mi
         } else {
-            MediaInfo::new(path)
-        }
+            MediaInfo::new(path, config, recursive, progress)?
+        };
+
+        mi.book_info = BookInfo::from_dir(path);
+        mi.cover = bookinfo::find_cover(path);
+        Ok(mi)
+    }
+
+    /// Scan the `path` and create new `MediaInfo`, ignoring ``gadacz_data.json`` even if it exists
+    pub fn fresh(
+        path: &Path,
+        config: &Config,
+        recursive: bool,
+        progress: &mut dyn FnMut(&str, usize, usize),
+    ) -> EyreResult<Self> {
+        let mut mi = MediaInfo::new(path, config, recursive, progress)?;
+        mi.book_info = BookInfo::from_dir(path);
+        mi.cover = bookinfo::find_cover(path);
+        Ok(mi)
     }
 
     /// Check if there is a `gadacz_data.json` under the `path`
     /// If true, read the data, add new file under the path, rescan all the all `Chapters`
     /// If false, create a  new `Mediainfo` from the files under the `path`
-    pub fn from_json(path: &Path, file: std::fs::DirEntry) -> EyreResult<Self> {
+    pub fn from_json(
+        path: &Path,
+        file: std::fs::DirEntry,
+        recursive: bool,
+        progress: &mut dyn FnMut(&str, usize, usize),
+    ) -> EyreResult<Self> {
         let data = std::fs::read_to_string(file.path())?;
         let mut mediainfo: MediaInfo = serde_json::from_str(&data)?;
+        mediainfo.is_recursive = mediainfo.is_recursive || recursive;
 
-        let content = scan_dir(path)?;
+        let content = scan_dir(path, path, mediainfo.is_recursive, &mut mediainfo.scan_errors)?;
 
         // Check if all files in the cached json are actually present in the directory
         let c = mediainfo
@@ -105,13 +190,20 @@ impl MediaInfo {
             ));
         }
 
-        // filter out files that are already inside the cached mediainfo and add the rest to the
-        // cached mediainfo chapters
-        let names: Vec<_> = mediainfo.chapters.iter().map(|it| it.filename.as_str()).collect();
+        // filter out files that are already inside the cached mediainfo or were removed by the user
+        // and add the rest to the cached mediainfo chapters
+        let names: Vec<_> = mediainfo
+            .chapters
+            .iter()
+            .map(|it| it.filename.as_str())
+            .chain(mediainfo.removed_files.iter().map(String::as_str))
+            .collect();
         let c: Vec<_> = content
             .iter()
-            .filter(|it| !names.contains(&it.file_name().unwrap().to_str().unwrap()))
+            .filter(|it| !names.contains(&super::relative_filename(it, path).as_str()))
+            .filter(|it| decodable(it, path, &mut mediainfo.scan_errors))
             .collect();
+        mediainfo.new_files = c.iter().map(|it| super::relative_filename(it, path)).collect();
 
         // handle new m4a / m4b files
         {
@@ -121,7 +213,7 @@ impl MediaInfo {
                 // already done in `scan_dir`
                 let ext = it.extension().unwrap().to_str().unwrap();
                 if M4_EXTENSIONS.contains(&ext) {
-                    let mut h = handle_m4(it, path)?;
+                    let mut h = m4_chapters_or_whole_file(it, path, &mut mediainfo.scan_errors);
                     new_chapters.append(&mut h);
                 }
             }
@@ -148,15 +240,26 @@ impl MediaInfo {
             mediainfo.chapters.append(&mut new_chapters);
         }
 
-        mediainfo.chaptercount = mediainfo.chapters.len();
-        mediainfo.scan_chapters(path);
-        mediainfo.sort_chapters();
+        mediainfo.scan_chapters(path, progress);
+        if !mediainfo.is_custom_order {
+            mediainfo.sort_chapters();
+        }
 
         Ok(mediainfo)
     }
 
-    pub fn new(path: &Path) -> EyreResult<Self> {
-        let content = scan_dir(path)?;
+    /// Scan the `path` and create new `MediaInfo`, the speed and volume are taken from the `config`
+    pub fn new(
+        path: &Path,
+        config: &Config,
+        recursive: bool,
+        progress: &mut dyn FnMut(&str, usize, usize),
+    ) -> EyreResult<Self> {
+        let mut scan_errors = Vec::new();
+        let content: Vec<_> = scan_dir(path, path, recursive, &mut scan_errors)?
+            .into_iter()
+            .filter(|it| decodable(it, path, &mut scan_errors))
+            .collect();
 
         if content.is_empty() {
             eyre::bail!("Given directory is empty or it has no files with valid extensions.")
@@ -183,38 +286,66 @@ impl MediaInfo {
             // already done in `scan_dir`
             let ext = it.extension().unwrap().to_str().unwrap();
             if M4_EXTENSIONS.contains(&ext) {
-                let mut h = handle_m4(it, path)?;
+                let mut h = m4_chapters_or_whole_file(it, path, &mut scan_errors);
                 chapters.append(&mut h);
             }
         }
 
         let mut mediainfo = Self {
             is_antispoiler: false,
+            is_fading: false,
+            is_reverse_advance: false,
+            intro_skip_secs: 0,
+            marked_position: None,
+            seek_step: default_seek_step(),
+            is_recursive: recursive,
+            is_custom_order: false,
+            book_note: None,
+            is_finished: false,
+            removed_files: Vec::new(),
             last_chapter: 0,
-            speed: 1.0,
-            volume: 0.5,
+            speed: config.speed,
+            volume: config.volume,
             path: path.to_owned(),
-            chaptercount: chapters.len(),
+            book_info: None,
+            cover: None,
+            new_files: Vec::new(),
+            scan_errors,
+            scan_timeouts: 0,
+            is_readonly: false,
             chapters,
         };
 
-        mediainfo.scan_chapters(path);
+        mediainfo.scan_chapters(path, progress);
         mediainfo.sort_chapters();
 
         Ok(mediainfo)
     }
 
-    /// Iterate over all the 'chapters' in 'self' scanning each 'Chapter' for gstreamer tags
-    fn scan_chapters(&mut self, path: &Path) {
-        let disc = gstreamer_pbutils::Discoverer::new(gst::ClockTime::from_seconds(1)).unwrap();
-        for it in &mut self.chapters {
-            it.get_info_from_tags(path, &disc)
+    /// Iterate over all the 'chapters' in 'self' scanning each 'Chapter' for gstreamer tags.
+    /// `progress` gets the file name, the number of the chapter and the number of all chapters
+    /// before each chapter is scanned, so a file the scan gets stuck on can be told. The files
+    /// that couldn't be read in time are counted in `scan_timeouts`
+    fn scan_chapters(&mut self, path: &Path, progress: &mut dyn FnMut(&str, usize, usize)) {
+        let disc = gstreamer_pbutils::Discoverer::new(DISCOVER_TIMEOUT).unwrap();
+        let count = self.chapters.len();
+        for (index, it) in self.chapters.iter_mut().enumerate() {
+            progress(&it.filename, index + 1, count);
+            if !it.get_info_from_tags(path, &disc) {
+                self.scan_timeouts += 1;
+            }
         }
     }
 
-    /// Sort by track number if tracknumber is not avaiable sort by title or filename
+    /// Sort by the subdirectory, then by track number if tracknumber is not avaiable sort by title
+    /// or filename
     fn sort_chapters(&mut self) {
         self.chapters.sort_by(|a, b| {
+            let folders = Path::new(&a.filename).parent().cmp(&Path::new(&b.filename).parent());
+            if folders != std::cmp::Ordering::Equal {
+                return folders;
+            }
+
             match (a.tracknumber.is_some(), b.tracknumber.is_some()) {
                 (true, true) => match a.tracknumber.cmp(&b.tracknumber) {
                     std::cmp::Ordering::Less => std::cmp::Ordering::Less,
@@ -238,13 +369,213 @@ impl MediaInfo {
         });
     }
 
+    /// Returns the number of chapters, always in sync with `chapters` since it isn't stored
+    pub fn chaptercount(&self) -> usize {
+        self.chapters.len()
+    }
+
+    /// Returns the speed of the chapter under `index`, its override or the speed of the book
+    pub fn speed_at(&self, index: usize) -> f64 {
+        self.chapters[index].speed_override.unwrap_or(self.speed)
+    }
+
+    /// Returns the volume of the chapter under `index`, its override or the volume of the book
+    pub fn volume_at(&self, index: usize) -> f64 {
+        self.chapters[index].volume_override.unwrap_or(self.volume)
+    }
+
+    /// Sets the speed of the chapter under `index`, changing its override if it has one and the
+    /// speed of the book otherwise
+    pub fn set_speed_at(&mut self, index: usize, speed: f64) {
+        match &mut self.chapters[index].speed_override {
+            Some(it) => *it = speed,
+            None => self.speed = speed,
+        }
+    }
+
+    /// Sets the volume of the chapter under `index`, changing its override if it has one and the
+    /// volume of the book otherwise
+    pub fn set_volume_at(&mut self, index: usize, volume: f64) {
+        match &mut self.chapters[index].volume_override {
+            Some(it) => *it = volume,
+            None => self.volume = volume,
+        }
+    }
+
+    /// Describes the chapters of files added since the last start and where they are in the
+    /// playlist, `None` if there are no such files
+    pub fn new_chapters_summary(&self) -> Option<String> {
+        if self.new_files.is_empty() {
+            return None;
+        }
+
+        let new: Vec<_> = self
+            .chapters
+            .iter()
+            .enumerate()
+            .filter(|(_, it)| self.new_files.contains(&it.filename))
+            .map(|(i, it)| format!("{} (#{})", it.get_name(), i + 1))
+            .collect();
+
+        Some(format!("Added {} new chapters: {}", new.len(), new.join(", ")))
+    }
+
     pub fn sort_all_bk(&mut self) {
         self.chapters
             .iter_mut()
             .for_each(|chapter| chapter.bookmarks.sort_by(|a, b| a.position.cmp(&b.position)))
     }
 
+    /// Moves every chapter back to its start and the book to the first chapter, bookmarks and
+    /// descriptions are kept
+    pub fn reset_progress(&mut self) {
+        self.last_chapter = 0;
+        self.is_finished = false;
+        for chapter in &mut self.chapters {
+            chapter.last_position = chapter.get_start_position();
+            chapter.z_position = None;
+            chapter.before_jump_position = None;
+        }
+    }
+
+    /// Returns the seconds listened to and the length of the whole book
+    pub fn progress(&self) -> (u64, u64) {
+        self.chapters
+            .iter()
+            .fold((0, 0), |(listened, total), it| (listened + it.listened(), total + it.length))
+    }
+
+    /// Returns the index of the furthest chapter with any progress, where the listening really
+    /// stopped regardless of jumping around the book
+    pub fn furthest_chapter(&self) -> Option<usize> {
+        self.chapters.iter().rposition(|it| it.completion() > 0.0)
+    }
+
+    /// Returns the index of the chapter with the track `number`. Chapters of a m4a/m4b file are
+    /// matched by their marker number within the file of the `current` chapter, loose files by
+    /// their track number
+    pub fn chapter_by_track_number(&self, current: usize, number: u32) -> Option<usize> {
+        let current = &self.chapters[current];
+        self.chapters.iter().position(|it| match current.m4_tracknumber {
+            Some(_) => it.filename == current.filename && it.m4_tracknumber == Some(number),
+            None => it.m4_tracknumber.is_none() && it.tracknumber == Some(number),
+        })
+    }
+
+    /// Returns the chapter and bookmark index of the first bookmark after `position` in `chapter`,
+    /// looking through the following chapters if needed
+    pub fn next_bookmark(&self, chapter: usize, position: u64) -> Option<(usize, usize)> {
+        self.bookmark_positions()
+            .filter(|&(ch, _, pos)| (ch, pos) > (chapter, position))
+            .min_by_key(|&(ch, _, pos)| (ch, pos))
+            .map(|(ch, bk, _)| (ch, bk))
+    }
+
+    /// Returns the chapter and bookmark index of the last bookmark before `position` in
+    /// `chapter`, looking through the preceding chapters if needed
+    pub fn previous_bookmark(&self, chapter: usize, position: u64) -> Option<(usize, usize)> {
+        self.bookmark_positions()
+            .filter(|&(ch, _, pos)| (ch, pos) < (chapter, position))
+            .max_by_key(|&(ch, _, pos)| (ch, pos))
+            .map(|(ch, bk, _)| (ch, bk))
+    }
+
+    /// Iterates over the chapter index, bookmark index and position of every bookmark
+    fn bookmark_positions(&self) -> impl Iterator<Item = (usize, usize, u64)> + '_ {
+        self.chapters.iter().enumerate().flat_map(|(ch, chapter)| {
+            chapter.bookmarks.iter().enumerate().map(move |(bk, it)| (ch, bk, it.position))
+        })
+    }
+
+    /// Returns the title of the book from `metadata.json` or the album tag, falling back to the
+    /// name of the directory
+    pub fn title(&self) -> String {
+        self.book_info
+            .as_ref()
+            .and_then(|it| it.title.clone())
+            .or_else(|| self.chapters.iter().find_map(|it| it.album.clone()))
+            .or_else(|| self.path.file_name().map(|it| it.to_string_lossy().into_owned()))
+            .unwrap_or_default()
+    }
+
+    /// Writes the descriptions and bookmarks of all the chapters to `notes.md` in the directory of
+    /// the book, returns the path of the file
+    pub fn export_notes(&self) -> EyreResult<PathBuf> {
+        self.write_export("notes.md", notes::to_markdown(&self.title(), &self.chapters))
+    }
+
+    /// Writes the bookmarks of all the chapters to `bookmarks.txt` in the directory of the book,
+    /// one per line, returns the path of the file
+    pub fn export_bookmarks(&self) -> EyreResult<PathBuf> {
+        let mut out = String::new();
+        for chapter in &self.chapters {
+            for bookmark in &chapter.bookmarks {
+                out.push_str(&format!(
+                    "{} | track {} | {}s | {}\n",
+                    chapter.get_name(),
+                    // loose files without a TrackNumber tag have no number
+                    chapter
+                        .m4_tracknumber
+                        .or(chapter.tracknumber)
+                        .map_or_else(|| "-".to_string(), |it| it.to_string()),
+                    bookmark.position,
+                    bookmark.formatted_position
+                ));
+            }
+        }
+
+        self.write_export("bookmarks.txt", out)
+    }
+
+    /// Produces a WebVTT document with a cue for every chapter, the cues of the chapters of m4a/m4b
+    /// files start at their marked positions, the rest follow each other
+    pub fn export_chapters_vtt(&self) -> String {
+        let mut out = String::from("WEBVTT\n");
+        let mut file_start = 0;
+        let mut end = 0;
+        let mut previous: Option<&str> = None;
+
+        for chapter in &self.chapters {
+            if previous != Some(chapter.filename.as_str()) {
+                file_start = end;
+                previous = Some(&chapter.filename);
+            }
+            let start = file_start + chapter.get_start_position();
+            end = start + chapter.length;
+
+            out.push_str(&format!(
+                "\n{} --> {}\n{}\n",
+                helpers::format_vtt_timestamp(start),
+                helpers::format_vtt_timestamp(end),
+                chapter.get_title_or_filename()
+            ));
+        }
+        out
+    }
+
+    /// Writes the chapters of the book to `chapters.vtt` next to `gadacz_data.json`, returns the
+    /// path of the file
+    pub fn export_chapters(&self) -> EyreResult<PathBuf> {
+        self.write_export("chapters.vtt", self.export_chapters_vtt())
+    }
+
+    /// Writes an exported file called `filename` into the directory of the book, fails in the
+    /// read-only mode so nothing is left in the directory
+    fn write_export(&self, filename: &str, contents: String) -> EyreResult<PathBuf> {
+        if self.is_readonly {
+            eyre::bail!("Read-only mode, {filename} wasn't written");
+        }
+
+        let path = self.path.join(filename);
+        std::fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    /// Writes `gadacz_data.json`, does nothing in the read-only mode
     pub fn save_to_file(&self) -> EyreResult<()> {
+        if self.is_readonly {
+            return Ok(());
+        }
         let json_as_string = serde_json::to_string(&self)?;
         let mut path = self.path.to_path_buf();
         path.push("gadacz_data.json");
@@ -254,6 +585,19 @@ impl MediaInfo {
     }
 }
 
+/// Reads the chapters of the m4a/m4b file, if that fails the whole file is a single chapter and
+/// the reason is added to `errors`, so one broken file doesn't stop the whole book from loading
+fn m4_chapters_or_whole_file(
+    file_path: &PathBuf,
+    path: &Path,
+    errors: &mut Vec<String>,
+) -> Vec<Chapter> {
+    handle_m4(file_path, path).unwrap_or_else(|err| {
+        errors.push(format!("{err}, playing it as a single chapter"));
+        vec![Chapter::new(file_path, path, None, None)]
+    })
+}
+
 #[cfg(not(feature = "mp4ameta"))]
 fn handle_m4(file_path: &PathBuf, path: &Path) -> EyreResult<Vec<Chapter>> {
     let m4_chapters: Vec<Chapter> = vec![Chapter::new(file_path, path, None, None)];
@@ -268,7 +612,9 @@ fn handle_m4(file_path: &PathBuf, path: &Path) -> EyreResult<Vec<Chapter>> {
     use crate::data::chapter::formatted_time;
     use crate::data::make_uri;
 
-    let mp4_tag = mp4ameta::Tag::read_from_path(file_path)?;
+    let name = super::relative_filename(file_path, path);
+    let mp4_tag = mp4ameta::Tag::read_from_path(file_path)
+        .map_err(|err| eyre::eyre!("Couldn't read the chapters of {name}: {err}"))?;
     let mut chapters: Vec<_> = mp4_tag.chapters().collect();
     let mut m4_chapters: Vec<Chapter> = Vec::new();
 
@@ -292,7 +638,7 @@ fn handle_m4(file_path: &PathBuf, path: &Path) -> EyreResult<Vec<Chapter>> {
 
         for (index, it) in chapters.iter().enumerate() {
             m4_chapters.push(Chapter::from_m4(
-                file_path.file_name().unwrap().to_str().unwrap().to_string(),
+                name.clone(),
                 Some(it.title.clone()),
                 Some(it.start.as_secs_f64().ceil() as u64),
                 Some(index as u32),
@@ -312,14 +658,17 @@ fn handle_m4(file_path: &PathBuf, path: &Path) -> EyreResult<Vec<Chapter>> {
             } else {
                 // case at the end of the file
                 // grabbing the length of the whole file
-                let disc =
-                    gstreamer_pbutils::Discoverer::new(gst::ClockTime::from_seconds(1)).unwrap();
-                let disc = disc.discover_uri(&make_uri(file_path)).unwrap();
-                let length = disc.duration().unwrap().seconds();
+                let length = gstreamer_pbutils::Discoverer::new(DISCOVER_TIMEOUT)
+                    .ok()
+                    .and_then(|disc| disc.discover_uri(&make_uri(file_path)).ok())
+                    .and_then(|info| info.duration())
+                    .ok_or_else(|| eyre::eyre!("Couldn't read the length of {name}"))?
+                    .seconds();
 
                 // figuring out the length of that chapter from the length of the whole
                 // file and the m4 chapter starter position
-                it.length = length - (it.start_position).unwrap();
+                // the start is rounded up so it can end up past the end of a short last chapter
+                it.length = length.saturating_sub((it.start_position).unwrap());
                 it.length_display = formatted_time(it.length);
             }
         }
@@ -328,32 +677,62 @@ fn handle_m4(file_path: &PathBuf, path: &Path) -> EyreResult<Vec<Chapter>> {
     Ok(m4_chapters)
 }
 
+/// Returns whether gstreamer can decode the `file`, which is only checked for the
+/// `CHECKED_EXTENSIONS`. A file that can't be decoded is noted in `scan_errors`
+fn decodable(file: &Path, path: &Path, scan_errors: &mut Vec<String>) -> bool {
+    let ext = file.extension().and_then(|it| it.to_str()).unwrap_or_default();
+    if !CHECKED_EXTENSIONS.contains(&ext) {
+        return true;
+    }
+
+    let decodable = gstreamer_pbutils::Discoverer::new(DISCOVER_TIMEOUT)
+        .ok()
+        .and_then(|disc| disc.discover_uri(&super::make_uri(file)).ok())
+        .is_some();
+    if !decodable {
+        scan_errors.push(format!(
+            "Skipped {}, gstreamer can't decode it. A plugin may be missing",
+            super::relative_filename(file, path)
+        ));
+    }
+    decodable
+}
+
 /// scan the dir under the path for files with valid extensions
-/// Returns a Vec with
-fn scan_dir(path: &Path) -> Result<Vec<PathBuf>, Error> {
-    path.read_dir()?
-        .filter_map(|it| {
-            let it = match it {
-                std::result::Result::Ok(ok) => ok,
-                std::result::Result::Err(err) => return Some(Err(Error::FromIo(err))),
-            };
-
-            let file = it.path();
-
-            if file.is_dir() {
-                return None;
+/// Returns a Vec with them, files without an extension are skipped and noted in `skipped` by their
+/// names relative to the directory of the book at `root`
+fn scan_dir(
+    root: &Path,
+    path: &Path,
+    recursive: bool,
+    skipped: &mut Vec<String>,
+) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::new();
+    for it in path.read_dir()? {
+        let file = it?.path();
+
+        if file.is_dir() {
+            if recursive {
+                files.append(&mut scan_dir(root, &file, recursive, skipped)?);
             }
+            continue;
+        }
 
-            let ext = if let Some(ext) = file.extension() {
-                ext.to_str().unwrap()
-            } else {
-                return Some(Err(Error::NoExtentsion(file)));
-            };
-
-            if VALID_EXTENSIONS.contains(&ext) {
-                return Some(Ok(file));
+        let ext = match file.extension().and_then(|it| it.to_str()) {
+            Some(ext) => ext,
+            None => {
+                // hidden files like .DS_Store aren't worth a message
+                if !file.file_name().is_some_and(|it| it.to_string_lossy().starts_with('.')) {
+                    let name = super::relative_filename(&file, root);
+                    skipped.push(format!("Skipped {name}, it has no extension"));
+                }
+                continue;
             }
-            None
-        })
-        .collect::<Result<Vec<PathBuf>, Error>>()
+        };
+
+        if VALID_EXTENSIONS.contains(&ext) {
+            files.push(file);
+        }
+    }
+    Ok(files)
 }
@@ -1,8 +1,23 @@
 use std::path::Path;
 
+use gstreamer as gst;
+
+pub mod bookinfo;
 pub mod bookmarks;
 pub mod chapter;
+pub mod config;
 pub mod mediainfo;
+pub mod notes;
+
+/// How long discovering a single file can take before giving up on it. Kept short so books on
+/// slow or remote drives don't freeze the startup
+pub const DISCOVER_TIMEOUT: gst::ClockTime = gst::ClockTime::from_seconds(1);
+
+/// Returns the name of the `file` relative to the directory of the book at `root`, e.g.
+/// `CD1/01.mp3` for files in its subdirectories
+pub fn relative_filename(file: &Path, root: &Path) -> String {
+    file.strip_prefix(root).unwrap_or(file).to_str().unwrap().to_owned()
+}
 
 /// Given a ```path``` creates a string in a format needed by gstreamer
 pub fn make_uri(path: &Path) -> String {
@@ -0,0 +1,73 @@
+//! Export of descriptions and bookmarks to Markdown, so they can be read outside of the player
+
+use super::chapter::Chapter;
+use crate::helpers::format_position;
+
+/// Produces a Markdown document titled `title` with a heading for every chapter that has a
+/// description or bookmarks, followed by the descriptions as paragraphs and the bookmarks as a list
+pub fn to_markdown(title: &str, chapters: &[Chapter]) -> String {
+    let mut out = format!("# {}\n", title);
+
+    for chapter in chapters {
+        let descriptions: Vec<&str> = [&chapter.description, &chapter.desc_from_file]
+            .into_iter()
+            .flatten()
+            .map(|it| it.trim())
+            .filter(|it| !it.is_empty())
+            .collect();
+
+        if descriptions.is_empty() && chapter.bookmarks.is_empty() {
+            continue;
+        }
+
+        out.push_str(&format!("\n## {}\n", chapter.get_name()));
+
+        for it in descriptions {
+            out.push_str(&format!("\n{}\n", it));
+        }
+
+        if !chapter.bookmarks.is_empty() {
+            out.push('\n');
+            for bookmark in &chapter.bookmarks {
+                // positions are relative to the start of the chapter, not the file
+                let position = bookmark.position.saturating_sub(chapter.get_start_position());
+                out.push_str(&format!(
+                    "- `{}` {}\n",
+                    format_position(position, None),
+                    bookmark.name
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_chapters_with_notes_are_exported() {
+        let chapter = |title: &str| {
+            Chapter::from_m4(
+                "book.m4b".to_string(),
+                Some(title.to_string()),
+                None,
+                Some(1),
+                Some(600),
+            )
+        };
+        let mut first = chapter("First");
+        first.description = Some("Important ".to_string());
+        first.add_bookmark("quote".to_string(), 65);
+        let second = chapter("Second");
+        let mut third = chapter("Third");
+        third.add_bookmark("end".to_string(), 5);
+
+        let actual = to_markdown("Book", &[first, second, third]);
+        let expected =
+            "# Book\n\n## First\n\nImportant\n\n- `1m5s` quote\n\n## Third\n\n- `5s` end\n";
+        assert_eq!(actual, expected);
+    }
+}
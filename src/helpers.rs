@@ -11,15 +11,15 @@ pub fn try_into_seconds(input: &str) -> Option<u64> {
 
         match it {
             'h' => {
-                total += number.parse::<u64>().unwrap() * 3600;
+                total += number.parse::<u64>().ok()? * 3600;
                 number = String::new();
             }
             'm' => {
-                total += number.parse::<u64>().unwrap() * 60;
+                total += number.parse::<u64>().ok()? * 60;
                 number = String::new();
             }
             's' => {
-                total += number.parse::<u64>().unwrap();
+                total += number.parse::<u64>().ok()?;
                 number = String::new();
             }
             _ => return None,
@@ -32,6 +32,43 @@ pub fn try_into_seconds(input: &str) -> Option<u64> {
     }
 }
 
+/// Same as `try_into_seconds` but the input starts with `+` or `-`, returns the signed seconds
+pub fn try_into_relative_seconds(input: &str) -> Option<i64> {
+    let (sign, rest) = if let Some(rest) = input.strip_prefix('+') {
+        (1, rest)
+    } else if let Some(rest) = input.strip_prefix('-') {
+        (-1, rest)
+    } else {
+        return None;
+    };
+    try_into_seconds(rest).map(|secs| sign * secs as i64)
+}
+
+/// Turns the input of the arbitrary position popout into seconds from the start of the chapter.
+/// With a leading `+` or `-` it's relative to the `current` position and clamped to the chapter of
+/// `length`, without it it's the absolute position
+pub fn resolve_chapter_position(input: &str, current: u64, length: u64) -> Option<u64> {
+    match try_into_relative_seconds(input) {
+        Some(offset) => Some((current as i64 + offset).clamp(0, length as i64) as u64),
+        None => try_into_seconds(input),
+    }
+}
+
+/// Same as `format_position` but with `relative_only` the position in the file is left out and
+/// only the one relative to the start of the chapter is shown
+pub fn format_chapter_position(
+    position: u64,
+    start_position: Option<u64>,
+    relative_only: bool,
+) -> String {
+    match start_position {
+        Some(start_position) if relative_only => {
+            format_position(position.saturating_sub(start_position), None)
+        }
+        _ => format_position(position, start_position),
+    }
+}
+
 /// Produces a string in format `"xhymzs"` where `x`, `y`, `z` are ammount of hours, minutes or
 /// seconds respectively according to given `position` and `start_position`
 pub fn format_position(position: u64, start_position: Option<u64>) -> String {
@@ -93,6 +130,76 @@ pub fn format_position(position: u64, start_position: Option<u64>) -> String {
         format!("{}h{}m{}s", hours, minutes, seconds)
     }
 }
+
+/// Template used for naming bookmarks created without a prompt
+pub const DEFAULT_BOOKMARK_TEMPLATE: &str = "{chapter} @ {time}";
+
+/// Expands a bookmark name template, replacing `{chapter}` with the title of the chapter, `{time}`
+/// with the formatted position and `{n}` with the number of the bookmark
+pub fn expand_bookmark_template(template: &str, chapter: &str, position: u64, n: usize) -> String {
+    template
+        .replace("{chapter}", chapter)
+        .replace("{time}", &format_position(position, None))
+        .replace("{n}", &n.to_string())
+}
+
+/// Scores how well `pattern` fuzzy matches `text`, ignoring case. Every character of the `pattern`
+/// has to appear in the `text` in order. Consecutive matches and matches near the start score
+/// higher. Returns `None` if the `pattern` doesn't match
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0; // index into `text` where the next character is searched from
+    let mut first_match: Option<usize> = None;
+    let mut last_match: Option<usize> = None;
+
+    for p in pattern.to_lowercase().chars() {
+        let i = next + text[next..].iter().position(|&c| c == p)?;
+        score += match last_match {
+            Some(last) if last + 1 == i => 10,
+            _ => 1,
+        };
+        first_match.get_or_insert(i);
+        last_match = Some(i);
+        next = i + 1;
+    }
+
+    Some(score - first_match.unwrap_or(0) as i64)
+}
+
+/// Returns the current unix timestamp in seconds
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|it| it.as_secs())
+        .unwrap_or(0)
+}
+
+/// Produces a string like `"2 days ago"` describing how long ago the unix timestamp `then` was
+/// relative to `now`
+pub fn format_relative_time(then: u64, now: u64) -> String {
+    let secs = now.saturating_sub(then);
+
+    let (amount, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86399 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
+    };
+
+    if amount == 1 { format!("1 {} ago", unit) } else { format!("{} {}s ago", amount, unit) }
+}
+
+/// Produces a countdown string in format `"mm:ss"`, minutes aren't wrapped into hours
+pub fn format_countdown(secs: u64) -> String {
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+/// Produces a WebVTT timestamp in format `"hh:mm:ss.mmm"`
+pub fn format_vtt_timestamp(secs: u64) -> String {
+    format!("{:02}:{:02}:{:02}.000", secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,6 +212,32 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn string_into_seconds_without_number() {
+        assert_eq!(try_into_seconds("h"), None);
+        assert_eq!(try_into_seconds("1hm"), None);
+    }
+
+    #[test]
+    fn relative_chapter_positions() {
+        assert_eq!(try_into_relative_seconds("+30s"), Some(30));
+        assert_eq!(try_into_relative_seconds("-90s"), Some(-90));
+        assert_eq!(try_into_relative_seconds("90s"), None);
+        assert_eq!(resolve_chapter_position("+30s", 100, 600), Some(130));
+        assert_eq!(resolve_chapter_position("-90s", 100, 600), Some(10));
+        assert_eq!(resolve_chapter_position("-2m", 100, 600), Some(0));
+        assert_eq!(resolve_chapter_position("+10m", 100, 600), Some(600));
+        assert_eq!(resolve_chapter_position("5m", 100, 600), Some(300));
+        assert_eq!(resolve_chapter_position("-", 100, 600), None);
+    }
+
+    #[test]
+    fn chapter_position_without_the_absolute_one() {
+        assert_eq!(format_chapter_position(3725, Some(3600), true), "2m5s");
+        assert_eq!(format_chapter_position(3725, Some(3600), false), "2m5s(1h2m5s)");
+        assert_eq!(format_chapter_position(125, None, true), "2m5s");
+    }
+
     #[test]
     fn formatted_display0() {
         let data = 1;
@@ -162,4 +295,52 @@ mod tests {
         let expected = "0s";
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn bookmark_template() {
+        let actual = expand_bookmark_template("{chapter} @ {time}", "Chapter 1", 61, 3);
+        assert_eq!(actual, "Chapter 1 @ 1m1s");
+
+        let actual = expand_bookmark_template("Mark {n}", "Chapter 1", 61, 3);
+        assert_eq!(actual, "Mark 3");
+    }
+
+    #[test]
+    fn fuzzy_matching() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "chapter"), None);
+        assert_eq!(fuzzy_score("tp", "chapter"), None);
+
+        let consecutive = fuzzy_score("cha", "Chapter").unwrap();
+        let scattered = fuzzy_score("cpr", "Chapter").unwrap();
+        assert!(consecutive > scattered);
+
+        let start = fuzzy_score("end", "end of part one").unwrap();
+        let later = fuzzy_score("end", "the end").unwrap();
+        assert!(start > later);
+    }
+
+    #[test]
+    fn relative_time() {
+        let now = 1_000_000;
+        assert_eq!(format_relative_time(now - 30, now), "just now");
+        assert_eq!(format_relative_time(now - 60, now), "1 minute ago");
+        assert_eq!(format_relative_time(now - 3 * 3600, now), "3 hours ago");
+        assert_eq!(format_relative_time(now - 2 * 86400 - 5, now), "2 days ago");
+        assert_eq!(format_relative_time(now + 10, now), "just now");
+    }
+
+    #[test]
+    fn countdown() {
+        assert_eq!(format_countdown(0), "00:00");
+        assert_eq!(format_countdown(65), "01:05");
+        assert_eq!(format_countdown(90 * 60 + 9), "90:09");
+    }
+
+    #[test]
+    fn vtt_timestamp() {
+        assert_eq!(format_vtt_timestamp(0), "00:00:00.000");
+        assert_eq!(format_vtt_timestamp(65), "00:01:05.000");
+        assert_eq!(format_vtt_timestamp(36 * 3600 + 7 * 60 + 9), "36:07:09.000");
+    }
 }
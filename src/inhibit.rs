@@ -0,0 +1,55 @@
+//! Keeping the system awake during playback
+
+use std::process::{Child,
+                   Command,
+                   Stdio};
+
+/// Inhibits idling and sleeping of the system while it holds a `systemd-inhibit` process
+pub struct Inhibitor {
+    pub enabled: bool, // opt-in with `--inhibit-idle`
+    child: Option<Child>,
+}
+
+impl Inhibitor {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled, child: None }
+    }
+
+    /// Starts inhibiting if it is enabled and isn't already inhibiting
+    pub fn acquire(&mut self) -> std::io::Result<()> {
+        if !self.enabled || self.child.is_some() {
+            return Ok(());
+        }
+
+        let child = Command::new("systemd-inhibit")
+            .args([
+                "--what=idle:sleep",
+                "--who=gadacz",
+                "--why=Playing an audiobook",
+                "--mode=block",
+                "sleep",
+                "infinity",
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        self.child = Some(child);
+        Ok(())
+    }
+
+    /// Stops inhibiting
+    pub fn release(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
@@ -0,0 +1,148 @@
+//! Remapping of the keys of the main view
+//!
+//! Keys are remapped onto the built-in ones, e.g. `"x" = "l"` makes `x` move forwards like `l`
+//! does. Keys are written as a single character (`"l"`, `"L"`), with `C-` for Control (`"C-r"`)
+//! or as one of `space`, `left`, `right`, `up`, `down`, `home`, `end`. The `keys` of the global
+//! config are read first and `gadacz_keys.toml` in the directory of the book overrides them.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crossterm::event::{Event,
+                       KeyCode,
+                       KeyEvent,
+                       KeyModifiers};
+
+/// Key as matched by the main view, Shift is already part of the character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    ctrl: bool,
+}
+
+impl Key {
+    /// Parses the notation described in the module docs
+    pub fn parse(input: &str) -> Option<Self> {
+        let (ctrl, rest) = match input.strip_prefix("C-") {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, input),
+        };
+
+        let code = match rest {
+            "space" => KeyCode::Char(' '),
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return None,
+                }
+            }
+        };
+
+        Some(Self { code, ctrl })
+    }
+
+    fn from_event(event: &KeyEvent) -> Self {
+        Self { code: event.code, ctrl: event.modifiers.contains(KeyModifiers::CONTROL) }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Keymap(HashMap<Key, Key>);
+
+impl Keymap {
+    /// Merges the `global` bindings with the ones from `gadacz_keys.toml` under `dir`, the latter
+    /// take precedence. Returns the keymap and descriptions of the bindings that were skipped
+    pub fn load(global: &HashMap<String, String>, dir: &Path) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut errors = keymap.extend(global);
+
+        let file = dir.join("gadacz_keys.toml");
+        match std::fs::read_to_string(&file) {
+            Ok(data) => match toml::from_str::<HashMap<String, String>>(&data) {
+                Ok(local) => errors.append(&mut keymap.extend(&local)),
+                Err(err) => errors.push(format!("Couldn't parse {}: {err}", file.display())),
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => errors.push(format!("Couldn't read {}: {err}", file.display())),
+        }
+
+        (keymap, errors)
+    }
+
+    /// Adds the `bindings` replacing existing ones, returns descriptions of the invalid ones
+    fn extend(&mut self, bindings: &HashMap<String, String>) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (from, to) in bindings {
+            match (Key::parse(from), Key::parse(to)) {
+                (Some(from), Some(to)) => {
+                    self.0.insert(from, to);
+                }
+                _ => errors.push(format!("Invalid key binding {from} = {to}")),
+            }
+        }
+        errors
+    }
+
+    /// Returns the built-in key the pressed one is mapped onto, or the pressed key itself
+    pub fn translate(&self, event: KeyEvent) -> KeyEvent {
+        match self.0.get(&Key::from_event(&event)) {
+            Some(to) => {
+                let modifiers = if to.ctrl { KeyModifiers::CONTROL } else { KeyModifiers::NONE };
+                KeyEvent::new(to.code, modifiers)
+            }
+            None => event,
+        }
+    }
+
+    /// Same as `translate` for key events, other events are returned as they are
+    pub fn translate_event(&self, event: Event) -> Event {
+        match event {
+            Event::Key(key) => Event::Key(self.translate(key)),
+            event => event,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(from, to)| (from.to_string(), to.to_string())).collect()
+    }
+
+    #[test]
+    fn parse_keys() {
+        assert_eq!(Key::parse("l"), Some(Key { code: KeyCode::Char('l'), ctrl: false }));
+        assert_eq!(Key::parse("C-r"), Some(Key { code: KeyCode::Char('r'), ctrl: true }));
+        assert_eq!(Key::parse("C-"), None);
+        assert_eq!(Key::parse("space"), Some(Key { code: KeyCode::Char(' '), ctrl: false }));
+        assert_eq!(Key::parse("left"), Some(Key { code: KeyCode::Left, ctrl: false }));
+        assert_eq!(Key::parse("home"), Some(Key { code: KeyCode::Home, ctrl: false }));
+        assert_eq!(Key::parse("ll"), None);
+        assert_eq!(Key::parse(""), None);
+    }
+
+    #[test]
+    fn local_bindings_override_global() {
+        let mut keymap = Keymap::default();
+        let errors = keymap.extend(&bindings(&[("x", "l"), ("y", "h")]));
+        assert!(errors.is_empty());
+        let errors = keymap.extend(&bindings(&[("x", "C-r"), ("z", "nope")]));
+        assert_eq!(errors.len(), 1);
+
+        let x = keymap.translate(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+        assert_eq!((x.code, x.modifiers), (KeyCode::Char('r'), KeyModifiers::CONTROL));
+        let y = keymap.translate(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+        assert_eq!((y.code, y.modifiers), (KeyCode::Char('h'), KeyModifiers::NONE));
+        let l = keymap.translate(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE));
+        assert_eq!(l.code, KeyCode::Char('l'));
+    }
+}
@@ -19,24 +19,39 @@ use crossterm::terminal::{disable_raw_mode,
                           enable_raw_mode,
                           EnterAlternateScreen,
                           LeaveAlternateScreen};
+use data::bookmarks::Bookmark;
 use data::chapter::Chapter;
+use data::config::{Config,
+                   Confirm,
+                   SeekPastEnd};
 use data::mediainfo::MediaInfo;
 use gst::prelude::*;
 use gst::MessageType;
 use gstreamer as gst;
+use inhibit::Inhibitor;
+use keymap::Keymap;
 use msgs::Msgs;
-use player::Player;
+use player::{AudioSink,
+             Fade,
+             Player,
+             Skim};
+use socket::{Event as SocketEvent,
+             Socket};
 use tui::backend::{Backend,
                    CrosstermBackend};
 use tui::Terminal;
 use ui::{render,
          Ui};
 
+pub mod args; // Parsing command line arguments
 pub mod cache;
 pub mod data; // Handling data
 pub mod helpers;
+pub mod inhibit; // Keeping the system awake
+pub mod keymap; // Remapping keys
 pub mod msgs;
 pub mod player; // Handling playing audio
+pub mod socket; // Scripting interface
 pub mod ui; // Handling rendering UI
 
 /// Assuming that [`ControlFlow`] has unit type inside of it,  matches 'x' on [`ControlFlow`] enum
@@ -60,6 +75,38 @@ gadacz [OPTIONS] [PATH]
 OPTIONS:
 -a, --antispoiler   Turn on antispoiler mode (hides the names of the chapters and remaining number of chapters)
 -h, --help          Print help information
+-q, --quiet         Don't print the summary of the listening on exit
+-r, --recursive     Also play files in the subdirectories of PATH (e.g. CD1, CD2), remembered for
+                    the book
+    --ask-resume    Ask whether to resume where the book was left off or start over
+    --export-bookmarks
+                    Write all the bookmarks to bookmarks.txt in PATH and exit
+    --export-chapters
+                    Write the chapters as WebVTT cues to chapters.vtt in PATH and exit
+    --export-notes  Write the descriptions and bookmarks to notes.md in PATH and exit
+    --fresh         Ignore the saved gadacz_data.json and scan the files again, the saved file
+                    is only overwritten after confirming on exit
+    --readonly      Don't change the book, keys adding or deleting bookmarks and descriptions and
+                    other editing keys are disabled, gadacz_data.json is never written and
+                    nothing is exported into PATH
+    --beep          Ring the terminal bell when a chapter finishes
+    --sink <ELEMENT>
+                    Play through the ELEMENT gstreamer sink (e.g. pulsesink, pipewiresink,
+                    alsasink) instead of autoaudiosink
+    --sink-device <DEVICE>
+                    Set the device property of the sink chosen with --sink to DEVICE, both
+                    override sink and sink_device from the config
+    --bookmark-name <TEMPLATE>
+                    Name bookmarks added with A after TEMPLATE, where {{chapter}},
+                    {{time}} and {{n}} are replaced with the chapter, position and
+                    number of the bookmark (default \"{{chapter}} @ {{time}}\")
+    --inhibit-idle  Keep the system from idling and sleeping during playback (uses systemd-inhibit)
+    --socket <PATH> Create a Unix socket under PATH emitting playback events and accepting commands
+    --status-file <PATH>
+                    Periodically write a one-line status to PATH (for status bars)
+    --state-log <PATH>
+                    Append the state dumped with C-d to PATH (for bug reports)
+    --              Treat the next argument as the PATH even if it starts with -
 "
     );
 }
@@ -73,41 +120,88 @@ fn main() -> color_eyre::Result<()> {
 
     let args: Vec<String> = args.collect();
 
-    let mut path: Option<&str> = None;
-    let mut antispoiler_mode: bool = false;
-
     if args.is_empty() {
         return Err(eyre::eyre!("No argument provided")
             .suggestion("Provide a path to the directory you want to play."));
     }
 
-    for args in &args {
-        match args.as_str() {
-            "--help" | "-h" => {
-                print_help_menu();
-                return Ok(());
-            }
-            "--antispoiler" | "-a" => {
-                antispoiler_mode = true;
-            }
-            p => path = Some(p),
+    let args = match args::parse_args(args) {
+        Ok(args) => args,
+        Err(err) => {
+            return Err(eyre::eyre!(err).suggestion("Run gadacz --help to see the valid options."));
         }
+    };
+
+    if args.help {
+        print_help_menu();
+        return Ok(());
     }
 
-    let path = match path {
-        Some(path) => std::path::PathBuf::from(&path).canonicalize()?,
+    let path = match &args.path {
+        Some(path) => std::path::PathBuf::from(path).canonicalize()?,
         None => {
             return Err(eyre::eyre!("No path provided")
                 .suggestion("Provide a path to the directory you want to play."));
         }
     };
 
-    let mut mediainfo = MediaInfo::from_cache_or_new(&path)?;
+    // the global config is only used for books without their own `gadacz_data.json`
+    let (config, config_error) = match Config::load() {
+        Ok(config) => (config, None),
+        Err(err) => (Config::default(), Some(err)),
+    };
+    let mut progress = print_scan_progress;
+    let mut mediainfo = if args.fresh {
+        MediaInfo::fresh(&path, &config, args.recursive, &mut progress)?
+    } else {
+        MediaInfo::from_cache_or_new(&path, &config, args.recursive, &mut progress)?
+    };
     mediainfo.sort_all_bk();
+    if mediainfo.scan_timeouts != 0 {
+        println!(
+            "Couldn't read {} files in time, their tags are skipped and their lengths will be \
+             read when they are played",
+            mediainfo.scan_timeouts
+        );
+    }
+
+    mediainfo.is_readonly = args.readonly;
+    if args.export_notes || args.export_bookmarks || args.export_chapters {
+        if args.export_notes {
+            let file = mediainfo.export_notes()?;
+            println!("Exported the notes to {}", file.display());
+        }
+        if args.export_bookmarks {
+            let file = mediainfo.export_bookmarks()?;
+            println!("Exported the bookmarks to {}", file.display());
+        }
+        if args.export_chapters {
+            let file = mediainfo.export_chapters()?;
+            println!("Exported the chapters to {}", file.display());
+        }
+        return Ok(());
+    }
+
+    mediainfo.is_antispoiler = mediainfo.is_antispoiler || args.antispoiler;
 
-    mediainfo.is_antispoiler = mediainfo.is_antispoiler || antispoiler_mode;
+    let socket = match &args.socket {
+        Some(p) => Some(Socket::bind(std::path::Path::new(p))?),
+        None => None,
+    };
+
+    // the flags take precedence over the config
+    let (sink, sink_device) = match args.sink {
+        Some(sink) => (Some(sink), args.sink_device),
+        None => (config.sink.clone(), args.sink_device.or_else(|| config.sink_device.clone())),
+    };
+    if sink.is_none() && sink_device.is_some() {
+        return Err(eyre::eyre!("A sink device given without a sink").suggestion(
+            "Choose the sink element with the --sink flag or with sink in the config.",
+        ));
+    }
+    let audio_sink = sink.map(|element| AudioSink::Element { element, device: sink_device });
 
-    let player = Player::default();
+    let player = Player::new(audio_sink.as_ref());
 
     // setup terminal
     enable_raw_mode()?;
@@ -119,7 +213,63 @@ fn main() -> color_eyre::Result<()> {
     terminal.hide_cursor()?;
 
     let mut app = App::new(player);
+    app.inhibitor.enabled = args.inhibit_idle;
+    app.socket = socket;
+    app.status_file = args.status_file.map(std::path::PathBuf::from);
+    app.state_log = args.state_log.map(std::path::PathBuf::from);
+    app.beep = args.beep;
+    app.ask_resume = args.ask_resume;
+    app.fresh = args.fresh;
+    if args.fresh {
+        app.msgs.push("Ignoring the saved progress, started with --fresh".into());
+    }
+    if let Some(err) = config_error {
+        app.msgs.push_error(format!("{err}, using the default config").into());
+    }
+    let (keymap, errors) = Keymap::load(&config.keys, &path);
+    app.keymap = keymap;
+    for err in errors {
+        app.msgs.push(err.into());
+    }
+    app.volume_step = config.volume_step;
+    app.big_jump_secs = config.big_jump_secs;
+    app.msgs.timeout = Duration::from_secs(config.message_secs);
+    app.auto_advance = config.auto_advance;
+    app.wrap_around = config.wrap_around;
+    app.loop_book = config.loop_book;
+    app.relative_positions = config.relative_positions;
+    app.speed_presets = config.speed_presets;
+    let (progress_colors, errors) = ui::colors::progress_colors(&config.progress_colors);
+    app.progress_colors = progress_colors;
+    for err in errors {
+        app.msgs.push(err.into());
+    }
+    app.seek_past_end = config.seek_past_end;
+    app.confirm = config.confirm;
+    app.render_rate = Duration::from_millis(config.render_ms.max(1));
+    app.update_rate = Duration::from_millis(config.update_ms.max(1));
+    app.end_of_book_grace = Duration::from_secs(config.end_of_book_grace_secs);
+    app.pause_bookmarks = config.pause_bookmarks;
+    if let Some(audio_sink) = audio_sink {
+        if audio_sink.is_available() {
+            app.audio_sink = Some(audio_sink);
+        } else {
+            app.msgs.push(
+                format!("Couldn't create the {} sink, using autoaudiosink", audio_sink.name())
+                    .into(),
+            );
+        }
+    }
+    if let Some(template) = args.bookmark_template {
+        app.bookmark_template = template;
+    }
     app.load_chapter(mediainfo.last_chapter, &mediainfo);
+    if let Some(summary) = mediainfo.new_chapters_summary() {
+        app.msgs.push(summary.into());
+    }
+    for err in std::mem::take(&mut mediainfo.scan_errors) {
+        app.msgs.push_error(err.into());
+    }
 
     let res = run_app(&mut terminal, &mut app, mediainfo);
 
@@ -127,16 +277,78 @@ fn main() -> color_eyre::Result<()> {
     let err = disable_raw_mode();
     let err1 = execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture);
 
-    res?.save_to_file()?; // should retry to save the file or some prompt?
+    app.player.null();
+    let mediainfo = res?;
+
+    // the terminal is already restored here, so anything printed is visible to the user
+    if mediainfo.is_readonly {
+        eprintln!("Read-only mode, gadacz_data.json was left untouched");
+    } else if app.fresh && !confirm_overwrite(&mediainfo)? {
+        eprintln!("Left the saved progress untouched");
+    } else if let Err(err) = mediainfo.save_to_file() {
+        eprintln!("Couldn't save the progress: {err}. Retrying once");
+        std::thread::sleep(Duration::from_millis(500));
+        if let Err(err) = mediainfo.save_to_file() {
+            let file = mediainfo.path.join("gadacz_data.json");
+            return Err(err.wrap_err("Couldn't save the progress").suggestion(format!(
+                "Check that {} is writable and that there is free space left, the progress since \
+                 the last save is lost",
+                file.display()
+            )));
+        }
+        eprintln!("Saved the progress");
+    }
+
+    if !args.quiet {
+        print_summary(&mediainfo, &app);
+    }
 
     err?;
     err1?;
 
-    app.player.null();
-
     Ok(())
 }
 
+/// Prints where the book was left and how long it was listened to, for the scrollback
+fn print_summary(mediainfo: &MediaInfo, app: &App) {
+    let (listened, total) = mediainfo.progress();
+    let percent = (listened * 100).checked_div(total).unwrap_or(0);
+    let chapter = app.current_chapter_index + 1;
+
+    println!("{}", mediainfo.title());
+    if mediainfo.is_antispoiler {
+        println!("Chapter {chapter}, {percent}% of the book");
+    } else {
+        println!("Chapter {chapter} of {}, {percent}% of the book", mediainfo.chaptercount());
+    }
+    println!("Listened for {} this time", data::chapter::formatted_time(app.listened.as_secs()));
+}
+
+/// Shows the file being scanned on startup in place of the previous one, a file the scan gets stuck
+/// on stays visible
+fn print_scan_progress(filename: &str, number: usize, count: usize) {
+    use std::io::Write;
+
+    print!("\r\x1b[2KScanning: {filename} ({number}/{count})");
+    if number == count {
+        println!();
+    }
+    let _ = io::stdout().flush();
+}
+
+/// Asks on the restored terminal whether to overwrite ``gadacz_data.json`` with the state of a
+/// `--fresh` session
+fn confirm_overwrite(mediainfo: &MediaInfo) -> io::Result<bool> {
+    let file = mediainfo.path.join("gadacz_data.json");
+    if !file.exists() {
+        return Ok(true);
+    }
+    eprint!("Started with --fresh, overwrite {} with the current state? [y/N] ", file.display());
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
 fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
@@ -144,8 +356,10 @@ fn run_app<B: Backend>(
 ) -> color_eyre::Result<MediaInfo> {
     let mut mediainfo = mediainfo;
 
+    // the screen is redrawn every tick, the state of the player is only updated every update_rate
     let mut last_tick = Instant::now();
-    let tick_rate = Duration::from_millis(33);
+    let tick_rate = app.render_rate;
+    let mut last_update = Instant::now();
     app.current_chapter_index = mediainfo.last_chapter;
 
     let mut last_time_saved = Instant::now();
@@ -154,14 +368,26 @@ fn run_app<B: Backend>(
     let mut last_time_percentage_updated = Instant::now();
     let dur_between_percentage_updates = Duration::from_secs(30);
 
+    if app.ask_resume {
+        actions::ask_resume(app, &mediainfo, terminal, &mut last_tick, tick_rate)?;
+    }
+
     loop {
         terminal.draw(|f| render(f, app, &mediainfo))?;
         let timeout =
             tick_rate.checked_sub(last_tick.elapsed()).unwrap_or_else(|| Duration::from_secs(0));
 
         if crossterm::event::poll(timeout)? {
-            match event::read()? {
+            let event = app.keymap.translate_event(event::read()?);
+            if let Event::Key(_) = event {
+                app.msgs.unpin();
+            }
+            match event {
                 Event::Key(key) => match key.code {
+                    _ if mediainfo.is_readonly && actions::is_editing_key(&key) => {
+                        app.msgs.push("Read-only mode.".into());
+                    }
+
                     KeyCode::Char('?') => ui::popouts::help_menu::run(
                         terminal,
                         app,
@@ -170,14 +396,88 @@ fn run_app<B: Backend>(
                         tick_rate,
                     )?,
 
-                    KeyCode::Char('p' | ' ') => actions::toggle_play(app),
+                    KeyCode::Char('p') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::save_default_speed_and_volume(app, &mediainfo)
+                    }
+
+                    KeyCode::Char('p' | ' ') => actions::toggle_play(app, &mut mediainfo),
+
+                    KeyCode::Char('f') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::toggle_fading(app, &mut mediainfo);
+                    }
+
+                    KeyCode::Char('b') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::toggle_finished(app, &mut mediainfo);
+                    }
+
+                    KeyCode::Char('r') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::toggle_reverse_advance(app, &mut mediainfo);
+                    }
+
+                    KeyCode::Char('o') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::toggle_chapter_override(app, &mut mediainfo);
+                    }
+
+                    KeyCode::Char('e') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::set_chapter_speed(
+                            app,
+                            &mut mediainfo,
+                            terminal,
+                            &mut last_tick,
+                            tick_rate,
+                        )?;
+                    }
 
                     KeyCode::Char('q') => match_cflow!(actions::quit(app, &mut mediainfo)),
 
+                    // in raw mode Ctrl+C doesn't send SIGINT, so it has to be handled like 'q' to
+                    // restore the terminal and save the position
+                    KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => {
+                        match_cflow!(actions::quit(app, &mut mediainfo))
+                    }
+
+                    KeyCode::Char('j') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::last_chapter(app, &mut mediainfo);
+                    }
+
+                    KeyCode::Char('k') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::first_chapter(app, &mut mediainfo);
+                    }
+
+                    KeyCode::Home => actions::first_chapter(app, &mut mediainfo),
+
+                    KeyCode::End => actions::last_chapter(app, &mut mediainfo),
+
                     KeyCode::Char('j') | KeyCode::Down => {
                         match_cflow!(actions::next_chapter(app, &mut mediainfo, true));
                     }
 
+                    KeyCode::Char('d') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::dump_state(app, &mediainfo);
+                    }
+
+                    KeyCode::Char('g') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::show_audio_info(app);
+                    }
+
+                    KeyCode::Char('n') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::edit_book_note(
+                            app,
+                            &mut mediainfo,
+                            terminal,
+                            &mut last_tick,
+                            tick_rate,
+                        )?
+                    }
+
+                    KeyCode::Char('n') => actions::show_book_note(
+                        app,
+                        &mediainfo,
+                        terminal,
+                        &mut last_tick,
+                        tick_rate,
+                    )?,
+
                     KeyCode::Char('d') => actions::add_description(
                         app,
                         &mut mediainfo,
@@ -186,7 +486,13 @@ fn run_app<B: Backend>(
                         tick_rate,
                     )?,
 
-                    KeyCode::Char('D') => actions::delete_description(app, &mut mediainfo),
+                    KeyCode::Char('D') => actions::delete_description(
+                        app,
+                        &mut mediainfo,
+                        terminal,
+                        &mut last_tick,
+                        tick_rate,
+                    )?,
 
                     KeyCode::Char('a') if key.modifiers == KeyModifiers::CONTROL => {
                         mediainfo.is_antispoiler = !mediainfo.is_antispoiler;
@@ -201,7 +507,9 @@ fn run_app<B: Backend>(
                         tick_rate
                     )?),
 
-                    KeyCode::Char('m') => actions::add_mark(app),
+                    KeyCode::Char('m') => actions::add_mark(app, &mut mediainfo),
+
+                    KeyCode::Char('A') => actions::add_quick_bookmark(app, &mut mediainfo),
 
                     KeyCode::Char('M') => actions::add_bookmark_at_mark(
                         app,
@@ -213,6 +521,54 @@ fn run_app<B: Backend>(
 
                     KeyCode::Char('k') | KeyCode::Up => actions::prev_chapter(app, &mut mediainfo),
 
+                    KeyCode::Char('J') => actions::move_chapter(app, &mut mediainfo, true),
+
+                    KeyCode::Char('K') => actions::move_chapter(app, &mut mediainfo, false),
+
+                    KeyCode::Char('X') => actions::remove_chapter(
+                        app,
+                        &mut mediainfo,
+                        terminal,
+                        &mut last_tick,
+                        tick_rate,
+                    )?,
+
+                    KeyCode::Char('c') => actions::recenter_playlist(app),
+
+                    KeyCode::Char('t') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::set_sleep_timer(
+                            app,
+                            &mediainfo,
+                            terminal,
+                            &mut last_tick,
+                            tick_rate,
+                        )?;
+                    }
+
+                    KeyCode::Char('x') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::cancel_sleep_timer(app);
+                    }
+
+                    KeyCode::Char('t') => actions::toggle_filenames(app),
+
+                    KeyCode::Char('O') => ui::popouts::devices::run(
+                        terminal,
+                        app,
+                        &mediainfo,
+                        &mut last_tick,
+                        tick_rate,
+                    )?,
+
+                    KeyCode::Char('I') => actions::toggle_compact_info(app),
+
+                    KeyCode::Char('e') => actions::toggle_session_time(app),
+
+                    KeyCode::Char('N') => actions::toggle_auto_advance(app),
+
+                    KeyCode::Char('T') => actions::trim_titles(app, &mut mediainfo),
+
+                    KeyCode::Char('o') => actions::toggle_completion_order(app, &mediainfo),
+
                     KeyCode::Char(',') => {
                         actions::restore_pos_and_chap_before_jump(app, &mut mediainfo)
                     }
@@ -227,10 +583,24 @@ fn run_app<B: Backend>(
 
                     KeyCode::Char(':') => actions::restore_pos_before_jump(app, &mut mediainfo),
 
+                    KeyCode::Char('l') if key.modifiers == KeyModifiers::CONTROL => {
+                        actions::set_seek_step(
+                            app,
+                            &mut mediainfo,
+                            terminal,
+                            &mut last_tick,
+                            tick_rate,
+                        )?;
+                    }
+
                     KeyCode::Char('l') | KeyCode::Right => {
                         actions::move_forward(app, &mut mediainfo);
                     }
 
+                    KeyCode::Char('H') => actions::big_jump(app, &mediainfo, false),
+
+                    KeyCode::Char('L') => actions::big_jump(app, &mediainfo, true),
+
                     KeyCode::Char('h') | KeyCode::Left => {
                         actions::move_backward(app, &mut mediainfo);
                     }
@@ -246,15 +616,8 @@ fn run_app<B: Backend>(
                     KeyCode::Char('F') => {
                         app.player.pause();
 
-                        let chap = app.get_mut_current_chapter(&mut mediainfo);
-
-                        if let Some(start_position) = chap.start_position {
-                            // if `start_position` exists the file is a mp4 file so it has to be
-                            // handled bit differently
-                            chap.update_last_position(start_position + chap.length);
-                        } else {
-                            chap.update_last_position(chap.length);
-                        }
+                        let file_duration = app.player.get_duration_sec();
+                        app.get_mut_current_chapter(&mut mediainfo).mark_complete(file_duration);
 
                         if let ControlFlow::Continue(_) =
                             actions::next_chapter(app, &mut mediainfo, false)
@@ -272,10 +635,9 @@ fn run_app<B: Backend>(
                             tick_rate,
                             "Input speed. Bigger than 0.0",
                             None,
-                            30,
                         )? {
                             if let Ok(speed) = input.parse::<f64>() {
-                                mediainfo.speed = speed;
+                                mediainfo.set_speed_at(app.current_chapter_index, speed);
                                 if app.player.set_speed(speed).is_err() {
                                     app.msgs.push("Couldn't set the speed".into());
                                 }
@@ -289,9 +651,15 @@ fn run_app<B: Backend>(
                         };
                     }
 
-                    KeyCode::Char('s') => actions::increase_speed(app, &mut mediainfo),
+                    KeyCode::Char('s') => actions::increase_speed(app, &mut mediainfo, 0.25),
 
-                    KeyCode::Char('S') => actions::descrease_speed(app, &mut mediainfo),
+                    KeyCode::Char(']') => actions::increase_speed(app, &mut mediainfo, 0.05),
+
+                    KeyCode::Char('S') => actions::descrease_speed(app, &mut mediainfo, 0.25),
+
+                    KeyCode::Char('[') => actions::descrease_speed(app, &mut mediainfo, 0.05),
+
+                    KeyCode::Char('x') => actions::cycle_speed(app, &mut mediainfo),
 
                     KeyCode::Char('r') => {
                         let res = ui::popouts::yn::run(
@@ -303,15 +671,20 @@ fn run_app<B: Backend>(
                             "Are you sure you want to reset the current chapter? y/n",
                         )?;
                         if res {
-                            app.player
-                                .seek_seconds(
-                                    app.get_current_chapter(&mediainfo).start_position.unwrap_or(0),
-                                    mediainfo.speed,
-                                )
-                                .unwrap();
+                            let start_position =
+                                app.get_current_chapter(&mediainfo).get_start_position();
+                            app.seek_in_chapter(start_position, &mediainfo).unwrap();
                         }
                     }
 
+                    KeyCode::Char('U') => actions::restart_book(
+                        app,
+                        &mut mediainfo,
+                        terminal,
+                        &mut last_tick,
+                        tick_rate,
+                    )?,
+
                     KeyCode::Char('b') => {
                         ui::popouts::bookmarks::run(
                             terminal,
@@ -322,6 +695,40 @@ fn run_app<B: Backend>(
                         )?;
                     }
 
+                    KeyCode::Char('\'') => actions::repeat_bookmark_jump(app, &mut mediainfo),
+                    KeyCode::Char('w') => actions::skim(app),
+                    KeyCode::Char('u') => actions::set_ab_point(app, &mediainfo),
+                    KeyCode::Char('>') => actions::jump_to_bookmark(app, &mut mediainfo, true),
+                    KeyCode::Char('<') => actions::jump_to_bookmark(app, &mut mediainfo, false),
+
+                    KeyCode::Char('E') => actions::export_notes(app, &mut mediainfo),
+
+                    KeyCode::Char('f') => ui::popouts::find_chapter::run(
+                        terminal,
+                        app,
+                        &mut mediainfo,
+                        &mut last_tick,
+                        tick_rate,
+                    )?,
+
+                    KeyCode::Char('g') => actions::go_to_chapter(
+                        app,
+                        &mut mediainfo,
+                        terminal,
+                        &mut last_tick,
+                        tick_rate,
+                        false,
+                    )?,
+
+                    KeyCode::Char('G') => actions::go_to_chapter(
+                        app,
+                        &mut mediainfo,
+                        terminal,
+                        &mut last_tick,
+                        tick_rate,
+                        true,
+                    )?,
+
                     KeyCode::Char('B') => ui::popouts::all_bookmarks::run(
                         terminal,
                         app,
@@ -330,6 +737,14 @@ fn run_app<B: Backend>(
                         tick_rate,
                     )?,
 
+                    KeyCode::Char('/') => ui::popouts::find_bookmark::run(
+                        terminal,
+                        app,
+                        &mut mediainfo,
+                        &mut last_tick,
+                        tick_rate,
+                    )?,
+
                     KeyCode::Char('v') => {
                         if let Some(input) = ui::popouts::input::run(
                             terminal,
@@ -339,11 +754,10 @@ fn run_app<B: Backend>(
                             tick_rate,
                             "Input volume. Between 0 and 100",
                             None,
-                            31,
                         )? {
                             if let Ok(volume) = input.parse::<u64>() {
                                 let v = volume as f64 / 100.0;
-                                mediainfo.volume = v;
+                                mediainfo.set_volume_at(app.current_chapter_index, v);
                                 app.player.set_volume(v);
                             } else {
                                 app.msgs.push("Invalid input".into());
@@ -368,7 +782,7 @@ fn run_app<B: Backend>(
 
                     KeyCode::Char('Z') => {
                         if let Some(pos) = app.get_current_chapter(&mediainfo).z_position {
-                            if app.player.seek_seconds(pos, mediainfo.speed).is_err() {
+                            if app.seek_in_chapter(pos, &mediainfo).is_err() {
                                 app.msgs.push(
                                     format!("Couldn't move the saved position at {}", pos).into(),
                                 );
@@ -379,7 +793,20 @@ fn run_app<B: Backend>(
                         }
                     }
 
-                    KeyCode::Char('0') => {}
+                    KeyCode::Char('i') => actions::set_intro_skip(app, &mut mediainfo),
+
+                    KeyCode::Char('0') => actions::jump_to_chapter_start(app, &mut mediainfo),
+
+                    KeyCode::Char('P') => actions::peek_position(app, &mediainfo),
+                    KeyCode::Char('V') => actions::open_current_file(app, &mediainfo),
+
+                    KeyCode::Char('R') => actions::set_repeat_count(
+                        app,
+                        &mediainfo,
+                        terminal,
+                        &mut last_tick,
+                        tick_rate,
+                    )?,
 
                     _ => continue,
                 },
@@ -391,7 +818,14 @@ fn run_app<B: Backend>(
                 }
 
                 Event::Mouse(mouse) => match mouse.kind {
-                    event::MouseEventKind::Down(_) => actions::toggle_play(app),
+                    event::MouseEventKind::Down(_) => {
+                        match app.ui.chapter_bar_fraction(mouse.column, mouse.row) {
+                            Some(fraction) => {
+                                actions::seek_to_fraction(app, &mut mediainfo, fraction)
+                            }
+                            None => actions::toggle_play(app, &mut mediainfo),
+                        }
+                    }
 
                     event::MouseEventKind::ScrollUp => {
                         actions::increase_volume(app, &mut mediainfo);
@@ -409,9 +843,18 @@ fn run_app<B: Backend>(
         }
 
         if last_tick.elapsed() >= tick_rate {
+            app.msgs.on_tick();
+            last_tick = Instant::now();
+        }
+
+        if last_update.elapsed() >= app.update_rate {
             let now = Instant::now();
             app.on_tick(&mut mediainfo);
-            last_tick = now;
+            last_update = now;
+
+            if let Some(command) = app.socket.as_ref().and_then(Socket::try_recv) {
+                actions::run_socket_command(app, &mut mediainfo, command);
+            }
 
             if last_time_saved.elapsed() >= dur_between_saves {
                 last_time_saved = now;
@@ -422,12 +865,15 @@ fn run_app<B: Backend>(
                     continue;
                 }
 
-                match mediainfo.save_to_file() {
-                    Ok(_) => app.msgs.push("Saved the file".into()),
-                    Err(err) => {
-                        // the error will be probably too big
-                        app.msgs.push(format!("Failed to save the file with err {}", err).into());
-                        app.msgs.push(format!("{err}").into());
+                // in --fresh mode the saved file is only overwritten on exit
+                if !app.fresh && !mediainfo.is_readonly {
+                    match mediainfo.save_to_file() {
+                        Ok(_) => app.msgs.push("Saved the file".into()),
+                        Err(err) => {
+                            app.msgs.push_error(
+                                format!("Failed to save the file with err {}", err).into(),
+                            );
+                        }
                     }
                 }
             }
@@ -440,7 +886,9 @@ fn run_app<B: Backend>(
                     continue;
                 }
                 last_time_percentage_updated = now;
-                app.cache.pl_percentages = None;
+                app.cache.invalidate_progress();
+
+                actions::write_status_file(app, &mediainfo);
             }
         }
     }
@@ -457,10 +905,90 @@ pub struct App<'a> {
     msgs: Msgs,
     ui: Ui,
     cache: Cache<'a>,
-    marked_position: Option<u64>, // position marked by the user with 'm' keybind
+    inhibitor: Inhibitor,   // keeps the system awake while playing
+    socket: Option<Socket>, // scripting interface enabled with `--socket`
+    status_file: Option<std::path::PathBuf>, // file for status bars set with `--status-file`
+    fade: Option<Fade>,     // fade in or out in progress
 
     /// position and chapter marked before making a jump form 'B' menu
     pos_and_chap_before_jump: Option<(u64, usize)>,
+
+    /// chapter and the bookmarks deleted last in the 'b' or 'B' menu, restored with 'u' there
+    deleted_bookmarks: Option<(usize, Vec<Bookmark>)>,
+
+    /// file the state dumped with 'C-d' is appended to, set with `--state-log`
+    state_log: Option<std::path::PathBuf>,
+
+    /// if true the terminal bell rings when a chapter finishes, set with `--beep`
+    beep: bool,
+    /// previously entered inputs keyed by the prompt they were entered into, oldest first
+    input_history: std::collections::HashMap<&'static str, Vec<String>>,
+    /// template for names of bookmarks added without a prompt, set with `--bookmark-name`
+    bookmark_template: String,
+    /// if true on startup the user is asked whether to resume or start over, set with
+    /// `--ask-resume`
+    ask_resume: bool,
+    /// if true `gadacz_data.json` was ignored on startup and is only written after confirming on
+    /// exit, set with `--fresh`
+    fresh: bool,
+    /// sink the playback goes through, set with `--sink` and `--sink-device` or in the config
+    audio_sink: Option<AudioSink>,
+    /// when the current listening session started, reset after a long pause
+    session_start: Instant,
+    /// how long the playback went on since the start, shown in the summary on exit
+    listened: Duration,
+    /// when `on_tick` last ran, to add the time since then to `listened`
+    last_update: Instant,
+    /// when the playback was last paused
+    paused_at: Option<Instant>,
+    /// how much + and - change the volume, read from the config
+    volume_step: f64,
+    /// how far H and L move, read from the config
+    big_jump_secs: u64,
+    /// if true the next chapter starts when the current one ends, read from the config
+    auto_advance: bool,
+    /// which destructive actions ask for a confirmation, read from the config
+    confirm: Confirm,
+    /// time between redraws, read from the config
+    render_rate: Duration,
+    /// time between queries of the player and other state updates, read from the config
+    update_rate: Duration,
+    /// when the playback gets paused by the sleep timer
+    sleep_deadline: Option<Instant>,
+    /// keys of the main view remapped by the config and `gadacz_keys.toml`
+    keymap: Keymap,
+    /// if true j and k go around from the last chapter to the first and back, read from the config
+    wrap_around: bool,
+    /// if true the first chapter starts after the last one ends, read from the config
+    loop_book: bool,
+    /// chapter and position of the bookmark selected last, to jump to it again with '\''
+    last_bookmark: Option<(usize, u64)>,
+    /// how long the playback goes on after the end of the book, read from the config
+    end_of_book_grace: Duration,
+    /// how many bookmarks added on pause are kept per chapter, read from the config
+    pause_bookmarks: usize,
+    /// when the last chapter ended, cleared once the playback is paused
+    book_ended_at: Option<Instant>,
+    /// speed ramp of skimming with 'w', kept going while the key is held
+    skim: Option<Skim>,
+    /// chapter index and the absolute A and B positions of the loop set with 'u', cleared when
+    /// the chapter changes
+    ab_loop: Option<(usize, u64, Option<u64>)>,
+    /// how many more times the current chapter is played from its start before going on, set
+    /// with 'R' and cleared when another chapter is loaded
+    repeat_remaining: u32,
+    /// chapter queued to be played right after the current one without a gap and its uri
+    gapless_next: Option<(usize, String)>,
+    /// if true positions are shown relative to the start of the chapter without the absolute
+    /// position in the file, read from the config
+    relative_positions: bool,
+    /// speeds 'x' cycles through, read from the config
+    speed_presets: Vec<f64>,
+    /// colors of the percentages in the playlist from the highest threshold, read from the config
+    progress_colors: Vec<(u16, tui::style::Color)>,
+    /// whether moving forwards past the end of a chapter goes on to the next one, read from the
+    /// config
+    seek_past_end: SeekPastEnd,
 }
 
 impl<'app> App<'app> {
@@ -472,25 +1000,182 @@ impl<'app> App<'app> {
             index_bookmark: None,
             cache: Cache::new(),
             ui: ui::Ui::new(),
-            marked_position: None,
             index_all_bookmark: None,
             pos_and_chap_before_jump: None,
+            deleted_bookmarks: None,
+            inhibitor: Inhibitor::new(false),
+            socket: None,
+            status_file: None,
+            fade: None,
+            state_log: None,
+            beep: false,
+            input_history: std::collections::HashMap::new(),
+            bookmark_template: helpers::DEFAULT_BOOKMARK_TEMPLATE.to_owned(),
+            ask_resume: false,
+            fresh: false,
+            audio_sink: None,
+            session_start: Instant::now(),
+            listened: Duration::ZERO,
+            last_update: Instant::now(),
+            paused_at: None,
+            volume_step: 0.05,
+            big_jump_secs: 300,
+            auto_advance: true,
+            confirm: Confirm::default(),
+            render_rate: Duration::from_millis(33),
+            update_rate: Duration::from_millis(200),
+            sleep_deadline: None,
+            keymap: Keymap::default(),
+            last_bookmark: None,
+            wrap_around: false,
+            loop_book: false,
+            end_of_book_grace: Duration::ZERO,
+            pause_bookmarks: 0,
+            book_ended_at: None,
+            skim: None,
+            ab_loop: None,
+            repeat_remaining: 0,
+            gapless_next: None,
+            relative_positions: false,
+            speed_presets: Vec::new(),
+            progress_colors: Vec::new(),
+            seek_past_end: SeekPastEnd::Stop,
         }
     }
 
     fn load_chapter(&mut self, chapter_index: usize, mediainfo: &MediaInfo) {
-        self.cache.invalide_all();
+        self.cache.invalidate_all();
+        self.book_ended_at = None;
+        self.gapless_next = None;
+        self.repeat_remaining = 0;
 
         self.player.null();
-        self.player = Player::default();
+        self.player = Player::new(self.audio_sink.as_ref());
         self.current_chapter_index = chapter_index;
         let current_chapter = self.get_current_chapter(mediainfo);
         self.player.load_chapter(
             current_chapter,
             &mediainfo.path,
-            mediainfo.speed,
-            mediainfo.volume,
+            mediainfo.speed_at(chapter_index),
+            mediainfo.volume_at(chapter_index),
+            mediainfo.intro_skip_secs,
+        );
+
+        self.announce_chapter(mediainfo);
+    }
+
+    /// Returns the index of the next chapter if it can start right after the current one without
+    /// reloading the player: it's in another file which is played from its start at the same speed
+    /// and volume
+    fn gapless_candidate(&self, mediainfo: &MediaInfo) -> Option<usize> {
+        let index = self.current_chapter_index + 1;
+        let next = mediainfo.chapters.get(index)?;
+        let current = self.get_current_chapter(mediainfo);
+
+        let gapless = self.auto_advance
+            && self.repeat_remaining == 0
+            && next.filename != current.filename
+            && next.get_start_position() == 0
+            && next.last_position == 0
+            && mediainfo.intro_skip_secs == 0
+            && mediainfo.speed_at(index) == mediainfo.speed_at(self.current_chapter_index)
+            && mediainfo.volume_at(index) == mediainfo.volume_at(self.current_chapter_index);
+        if gapless { Some(index) } else { None }
+    }
+
+    /// Switches to the chapter `index` whose file the player already went on to without a gap
+    fn gapless_advance(&mut self, index: usize, mediainfo: &mut MediaInfo) {
+        self.get_mut_current_chapter(mediainfo).mark_complete(None);
+        self.chapter_finished_cue();
+        self.msgs.push("End of the chapter. Starting next chapter".into());
+
+        self.cache.invalidate_all();
+        self.book_ended_at = None;
+        self.current_chapter_index = index;
+
+        // the rate set by a seek only lasts until the end of the file it was done in
+        let speed = mediainfo.speed_at(index);
+        if speed != 1.0 && self.player.set_speed(speed).is_err() {
+            self.msgs.push("Couldn't set the speed".into());
+        }
+
+        self.announce_chapter(mediainfo);
+    }
+
+    /// Tells the user and the socket about the chapter that just started
+    fn announce_chapter(&mut self, mediainfo: &MediaInfo) {
+        let chapter_index = self.current_chapter_index;
+        let current_chapter = self.get_current_chapter(mediainfo);
+        if let Some(last_played_at) = current_chapter.last_played_at {
+            self.msgs.push(
+                format!(
+                    "Last played {}",
+                    helpers::format_relative_time(last_played_at, helpers::unix_now())
+                )
+                .into(),
+            );
+        }
+
+        if let Some(socket) = &self.socket {
+            socket.emit(&SocketEvent::ChapterChange {
+                index: chapter_index,
+                title: current_chapter.get_name(),
+            });
+        }
+    }
+
+    /// Seeks to the absolute `position` clamped into the current chapter
+    fn seek_in_chapter(
+        &mut self,
+        position: u64,
+        mediainfo: &MediaInfo,
+    ) -> Result<(), glib::BoolError> {
+        let position = self.get_current_chapter(mediainfo).clamp_position(position);
+        self.player.seek_seconds(position, mediainfo.speed_at(self.current_chapter_index))
+    }
+
+    /// Deletes the bookmark, keeping it until the next deletion so it can be restored
+    fn delete_bookmark(&mut self, mediainfo: &mut MediaInfo, chapter_index: usize, index: usize) {
+        let deleted = mediainfo.chapters[chapter_index].delete_bookmark(index);
+        self.msgs.push(
+            format!("Deleted bookmark: {}. Press u to restore it", deleted.formatted_position)
+                .into(),
+        );
+        self.deleted_bookmarks = Some((chapter_index, vec![deleted]));
+        self.cache.invalidate_bks();
+        self.cache.pl_bks_count = None;
+    }
+
+    /// Deletes all the bookmarks of the chapter, keeping them until the next deletion so they can
+    /// be restored
+    fn delete_all_bookmarks(&mut self, mediainfo: &mut MediaInfo, chapter_index: usize) {
+        let deleted = std::mem::take(&mut mediainfo.chapters[chapter_index].bookmarks);
+        self.msgs.push(
+            format!("Deleted all {} bookmarks. Press u to restore them", deleted.len()).into(),
         );
+        self.deleted_bookmarks = Some((chapter_index, deleted));
+        self.cache.invalidate_bks();
+        self.cache.pl_bks_count = None;
+    }
+
+    /// Puts the bookmarks deleted last back into their chapter
+    fn restore_deleted_bookmark(&mut self, mediainfo: &mut MediaInfo) {
+        match self.deleted_bookmarks.take() {
+            Some((chapter_index, deleted)) => {
+                match deleted.as_slice() {
+                    [bookmark] => self
+                        .msgs
+                        .push(format!("Restored bookmark: {}", bookmark.formatted_position).into()),
+                    _ => self.msgs.push(format!("Restored {} bookmarks", deleted.len()).into()),
+                }
+                let bookmarks = &mut mediainfo.chapters[chapter_index].bookmarks;
+                bookmarks.extend(deleted);
+                bookmarks.sort_by_key(|it| it.position);
+                self.cache.invalidate_bks();
+                self.cache.pl_bks_count = None;
+            }
+            None => self.msgs.push("There is no deleted bookmark to restore".into()),
+        }
     }
 
     fn bookmark_select(
@@ -503,7 +1188,10 @@ impl<'app> App<'app> {
             self.load_chapter(track, mediainfo);
             let current_chapter = self.get_current_chapter(mediainfo);
             let bookmark = current_chapter.bookmarks.get(bookmark_index).unwrap();
-            self.player.seek_seconds(bookmark.position, mediainfo.speed).unwrap();
+            self.last_bookmark = Some((track, bookmark.position));
+            self.player
+                .seek_seconds(bookmark.position, mediainfo.speed_at(self.current_chapter_index))
+                .unwrap();
             if let Some(tracknumber) = current_chapter.tracknumber {
                 self.msgs.push(
                     format!(
@@ -527,13 +1215,34 @@ impl<'app> App<'app> {
         } else {
             let current_chapter = self.get_current_chapter(mediainfo);
             let bookmark = current_chapter.bookmarks.get(bookmark_index).unwrap();
+            self.last_bookmark = Some((self.current_chapter_index, bookmark.position));
 
-            self.player.seek_seconds(bookmark.position, mediainfo.speed).unwrap();
+            self.player
+                .seek_seconds(bookmark.position, mediainfo.speed_at(self.current_chapter_index))
+                .unwrap();
             self.msgs.push(format!("Selected bookmark: {}", bookmark.formatted_position,).into());
         }
     }
 
     fn on_tick(&mut self, mediainfo: &mut MediaInfo) {
+        let now = Instant::now();
+        if self.player.is_playing() {
+            self.listened += now - self.last_update;
+        }
+        self.last_update = now;
+
+        // the length couldn't be discovered when the book was scanned, so take it from the player
+        let current_chapter = self.get_mut_current_chapter(mediainfo);
+        if current_chapter.length == 0 && current_chapter.start_position.is_none() {
+            if let Some(duration) = self.player.playbin.query_duration::<gst::ClockTime>() {
+                current_chapter.set_length(duration.seconds());
+                self.cache.formatted_length = None;
+                self.cache.pl_lengths = None;
+                self.cache.pl_percentages = None;
+                self.cache.book_progress = None;
+            }
+        }
+
         let current_chapter = self.get_current_chapter(mediainfo);
 
         let (abs_position, position) = if let Some(abs_pos) = self.player.get_position_sec() {
@@ -546,31 +1255,117 @@ impl<'app> App<'app> {
             (0, 0)
         };
 
-        self.ui.on_tick(mediainfo.volume, position, current_chapter.length);
+        let volume = mediainfo.volume_at(self.current_chapter_index);
+        self.ui.on_tick(volume, position, current_chapter.length);
         self.cache.on_tick(current_chapter, position, abs_position);
-        self.msgs.on_tick();
-
-        // handle gstreamer messages
-        if let Some(msg) = self.player.bus.pop_filtered(&[MessageType::Eos, MessageType::Error]) {
-            use gst::MessageView;
-
-            match msg.view() {
-                MessageView::Eos(_) => {
-                    self.get_mut_current_chapter(mediainfo).update_last_position(abs_position);
 
-                    if self.current_chapter_index + 1 < mediainfo.chaptercount {
-                        self.msgs.push("End of stream. Starting next chapter".into());
+        if let Some(socket) = self.socket.as_mut() {
+            socket.emit_position(position, current_chapter.length);
+        }
 
-                        self.msgs.push(self.current_chapter_index.to_string().into());
+        if let Some(deadline) = self.sleep_deadline {
+            if Instant::now() >= deadline {
+                self.sleep_deadline = None;
+                if self.player.is_playing() {
+                    actions::stop_playback(self);
+                    self.msgs.push("Sleep timer expired".into());
+                }
+            }
+        }
+        self.cache.update_sleep_countdown(self.sleep_remaining());
+
+        if let Some(fade) = &self.fade {
+            let progress = fade.progress();
+            if progress >= 1.0 {
+                self.player.set_volume(volume);
+                if fade.is_out {
+                    actions::stop_playback(self);
+                }
+                self.fade = None;
+            } else if fade.is_out {
+                self.player.set_volume(volume * (1.0 - progress));
+            } else {
+                self.player.set_volume(volume * progress);
+            }
+        }
+
+        let speed = mediainfo.speed_at(self.current_chapter_index);
+        if let Some((chapter_index, a, b)) = self.ab_loop {
+            if chapter_index != self.current_chapter_index {
+                self.ab_loop = None;
+                self.msgs.push("Cleared the A-B loop, the chapter changed".into());
+            } else if matches!(b, Some(b) if abs_position >= b)
+                && self.player.is_playing()
+                && self.player.seek_seconds(a, speed).is_err()
+            {
+                self.msgs.push("Failed to seek back to the A point".into());
+            }
+        }
+
+        if let Some(skim) = self.skim.as_mut() {
+            let speed = match skim.step(speed) {
+                Some(skim_speed) => skim_speed,
+                None => {
+                    self.skim = None;
+                    speed
+                }
+            };
+            if let Err(err) = self.player.set_speed(speed) {
+                self.msgs.push(format!("Failed to set the speed: {err}").into());
+            }
+        }
+
+        let candidate = self.gapless_candidate(mediainfo);
+        if candidate != self.gapless_next.as_ref().map(|(index, _)| *index) {
+            self.gapless_next = candidate.map(|index| {
+                let path = mediainfo.path.join(&mediainfo.chapters[index].filename);
+                (index, data::make_uri(&path))
+            });
+            self.player.queue_next(self.gapless_next.as_ref().map(|(_, uri)| uri.clone()));
+        }
+
+        // handle gstreamer messages
+        if let Some(msg) = self.player.bus.pop_filtered(&[
+            MessageType::Eos,
+            MessageType::Error,
+            MessageType::StreamStart,
+        ]) {
+            use gst::MessageView;
+
+            match msg.view() {
+                // also posted when a chapter is loaded, only the queued file means going on
+                MessageView::StreamStart(_) => {
+                    if let Some((index, uri)) = &self.gapless_next {
+                        if self.player.current_uri().as_ref() == Some(uri) {
+                            let index = *index;
+                            self.gapless_next = None;
+                            self.gapless_advance(index, mediainfo);
+                        }
+                    }
+                }
+                MessageView::Eos(_) => {
+                    self.get_mut_current_chapter(mediainfo).update_last_position(abs_position);
+                    self.chapter_finished_cue();
+
+                    if self.repeat_remaining > 0 {
+                        self.repeat_chapter(mediainfo);
+                    } else if !self.auto_advance {
+                        self.msgs.push("End of the chapter".into());
+                        actions::stop_playback(self);
+                    } else if self.current_chapter_index + 1 < mediainfo.chaptercount() {
+                        self.msgs.push("End of stream. Starting next chapter".into());
+
+                        self.msgs.push(self.current_chapter_index.to_string().into());
                         self.load_chapter(self.current_chapter_index + 1, mediainfo);
                         self.player.play();
-                    } else {
-                        self.msgs.push("End of the book".into());
-                        self.player.pause();
+                    } else if self.loop_book {
+                        self.restart_book(mediainfo);
+                    } else if self.book_ended_at.is_none() {
+                        self.end_of_book(mediainfo);
                     }
                 }
                 MessageView::Error(err) => {
-                    self.msgs.push(
+                    self.msgs.push_error(
                         format!(
                             "Error from {:?}: {} ({:?})",
                             err.src().map(|s| s.path_string()),
@@ -583,17 +1378,112 @@ impl<'app> App<'app> {
 
                 _ => (),
             }
-        } else if self.player.is_playing() && position >= current_chapter.length {
+        } else if self.player.is_playing()
+            && self.book_ended_at.is_none()
+            && self.gapless_next.is_none()
+            && current_chapter.length != 0
+            && position >= current_chapter.length
+        {
             self.get_mut_current_chapter(mediainfo).update_last_position(abs_position);
-            if self.current_chapter_index + 1 < mediainfo.chaptercount {
+            self.chapter_finished_cue();
+            if self.repeat_remaining > 0 {
+                self.repeat_chapter(mediainfo);
+            } else if !self.auto_advance {
+                self.msgs.push("End of the chapter".into());
+                actions::stop_playback(self);
+            } else if self.current_chapter_index + 1 < mediainfo.chaptercount() {
                 self.msgs.push("End of the chapter. Starting next chapter".into());
                 self.load_chapter(self.current_chapter_index + 1, mediainfo);
                 self.player.play();
+            } else if self.loop_book {
+                self.restart_book(mediainfo);
             } else {
-                self.msgs.push("End of the book".into());
-                self.player.pause();
+                self.end_of_book(mediainfo);
             }
         }
+
+        if let Some(ended_at) = self.book_ended_at {
+            if ended_at.elapsed() >= self.end_of_book_grace {
+                self.book_ended_at = None;
+                if self.player.is_playing() {
+                    self.player.pause();
+                }
+            }
+        }
+    }
+
+    /// Plays the current chapter again from its start, one of the repeats set with 'R'
+    fn repeat_chapter(&mut self, mediainfo: &MediaInfo) {
+        self.repeat_remaining -= 1;
+        let start = self.get_current_chapter(mediainfo).get_start_position();
+        if self.seek_in_chapter(start, mediainfo).is_err() {
+            self.repeat_remaining = 0;
+            self.msgs.push("Couldn't move to the start of the chapter to repeat it".into());
+            actions::stop_playback(self);
+            return;
+        }
+        self.player.play();
+        self.msgs.push(
+            format!("Repeating the chapter, {} more times after this one", self.repeat_remaining)
+                .into(),
+        );
+    }
+
+    /// Starts the first chapter after the last one ended, set with `loop_book` in the config
+    fn restart_book(&mut self, mediainfo: &mut MediaInfo) {
+        // files which end right away would otherwise be cycled through on every tick
+        if mediainfo.chapters.iter().all(|it| it.length == 0) {
+            self.loop_book = false;
+            self.msgs.push("Stopped looping the book, none of the chapters has a length".into());
+            self.end_of_book(mediainfo);
+            return;
+        }
+        self.msgs.push("End of the book. Starting the first chapter".into());
+        self.load_chapter(0, mediainfo);
+        self.player.play();
+    }
+
+    /// Announces the end of the book on the socket and in the status file. The playback is paused
+    /// after the grace period from the config, until then the pipeline is left playing silence.
+    /// The book is marked as finished
+    fn end_of_book(&mut self, mediainfo: &mut MediaInfo) {
+        self.msgs.push("End of the book".into());
+        mediainfo.is_finished = true;
+        self.book_ended_at = Some(Instant::now());
+        if let Some(socket) = &self.socket {
+            socket.emit(&SocketEvent::EndOfBook);
+        }
+        actions::write_status_file(self, mediainfo);
+    }
+
+    /// Returns the seconds left until the sleep timer pauses the playback
+    fn sleep_remaining(&self) -> Option<u64> {
+        self.sleep_deadline.map(|it| it.saturating_duration_since(Instant::now()).as_secs())
+    }
+
+    /// Rings the terminal bell if it was turned on with `--beep`
+    fn chapter_finished_cue(&self) {
+        if self.beep {
+            let mut stdout = io::stdout();
+            let _ = io::Write::write_all(&mut stdout, b"\x07");
+            let _ = io::Write::flush(&mut stdout);
+        }
+    }
+
+    /// Summarizes the current state in a single line, meant to be pasted into bug reports
+    fn state_summary(&self, mediainfo: &MediaInfo) -> String {
+        format!(
+            "chapter: {}/{} | position: {:?} | speed: {} | volume: {} | state: {:?} | bookmarks: \
+             {} | antispoiler: {}",
+            self.current_chapter_index,
+            mediainfo.chaptercount(),
+            self.player.get_position_sec(),
+            mediainfo.speed_at(self.current_chapter_index),
+            mediainfo.volume_at(self.current_chapter_index),
+            self.player.state,
+            mediainfo.chapters.iter().fold(0, |acc, it| it.bookmarks.len() + acc),
+            mediainfo.is_antispoiler,
+        )
     }
 
     fn get_current_chapter<'a, 'b>(&'a self, mediainfo: &'b MediaInfo) -> &'b Chapter {
@@ -606,26 +1496,207 @@ impl<'app> App<'app> {
 }
 
 mod actions {
-    use std::io;
+    use std::io::{self,
+                  Write};
     use std::ops::ControlFlow;
     use std::time::{Duration,
                     Instant};
 
+    use crossterm::event::{KeyCode,
+                           KeyEvent,
+                           KeyModifiers};
     use tui::backend::Backend;
     use tui::Terminal;
 
+    use crate::data::chapter::formatted_time;
+    use crate::data::config::{Config,
+                              SeekPastEnd};
     use crate::data::mediainfo::MediaInfo;
+    use crate::player::{AudioSink,
+                        Fade,
+                        Player,
+                        Skim};
+    use crate::socket::{Command,
+                        Event as SocketEvent};
     use crate::{ui,
                 App};
 
-    /// toggles playback
-    pub fn toggle_play(app: &mut App) {
+    /// Pauses at least this long start a new listening session
+    const SESSION_BREAK: Duration = Duration::from_secs(30 * 60);
+
+    /// toggles playback, fading the volume in or out if fading is turned on. Pausing saves the
+    /// position to the file right away
+    pub fn toggle_play(app: &mut App, mediainfo: &mut MediaInfo) {
+        // toggling in the middle of a fade reverses it right away
+        if let Some(fade) = app.fade.take() {
+            app.player.set_volume(mediainfo.volume_at(app.current_chapter_index));
+            if fade.is_out {
+                app.msgs.push("Resumed Playback".into());
+            } else {
+                stop_playback(app);
+            }
+            return;
+        }
+
         if app.player.is_paused() {
-            app.player.play();
-            app.msgs.push("Starting Playback".into());
+            if mediainfo.is_fading {
+                app.player.set_volume(0.0);
+                app.fade = Some(Fade::new(false));
+            }
+            start_playback(app);
         } else if app.player.is_playing() {
-            app.player.pause();
-            app.msgs.push("Stopping Playback".into());
+            app.player.flush_position(app.get_mut_current_chapter(mediainfo));
+            if app.pause_bookmarks > 0 && !mediainfo.is_readonly {
+                if let Some(pos) = app.player.get_position_sec() {
+                    let keep = app.pause_bookmarks;
+                    app.get_mut_current_chapter(mediainfo).add_pause_bookmark(pos, keep);
+                    app.cache.invalidate_bks();
+                }
+            }
+            // in --fresh mode the saved file is only overwritten on exit
+            if !app.fresh {
+                if let Err(err) = mediainfo.save_to_file() {
+                    app.msgs.push_error(format!("Failed to save the file with err {err}").into());
+                }
+            }
+
+            if mediainfo.is_fading {
+                app.fade = Some(Fade::new(true)); // paused in `App::on_tick` once it finishes
+            } else {
+                stop_playback(app);
+            }
+        }
+    }
+
+    fn start_playback(app: &mut App) {
+        if let Some(paused_at) = app.paused_at.take() {
+            if paused_at.elapsed() >= SESSION_BREAK {
+                app.session_start = Instant::now();
+            }
+        }
+        app.player.play();
+        app.msgs.push("Starting Playback".into());
+        if let Some(socket) = &app.socket {
+            socket.emit(&SocketEvent::Play);
+        }
+        if let Err(err) = app.inhibitor.acquire() {
+            app.msgs.push(format!("Couldn't inhibit idling: {err}").into());
+        }
+    }
+
+    pub fn stop_playback(app: &mut App) {
+        app.paused_at = Some(Instant::now());
+        app.player.pause();
+        app.msgs.push("Stopping Playback".into());
+        if let Some(socket) = &app.socket {
+            socket.emit(&SocketEvent::Pause);
+        }
+        app.inhibitor.release();
+    }
+
+    pub fn toggle_fading(app: &mut App, mediainfo: &mut MediaInfo) {
+        mediainfo.is_fading = !mediainfo.is_fading;
+        if mediainfo.is_fading {
+            app.msgs.push("Turned on fading in and out on play and pause".into());
+        } else {
+            app.msgs.push("Turned off fading in and out on play and pause".into());
+        }
+    }
+
+    /// Marks the book as finished or not finished, the book is marked as finished on its own when
+    /// it ends
+    pub fn toggle_finished(app: &mut App, mediainfo: &mut MediaInfo) {
+        mediainfo.is_finished = !mediainfo.is_finished;
+        if mediainfo.is_finished {
+            app.msgs.push("Marked the book as finished".into());
+        } else {
+            app.msgs.push("Marked the book as not finished".into());
+        }
+    }
+
+    /// Shows a summary of the current state and appends it to the file given with `--state-log`
+    pub fn dump_state(app: &mut App, mediainfo: &MediaInfo) {
+        let summary = app.state_summary(mediainfo);
+
+        if let Some(path) = &app.state_log {
+            let res = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{summary}"));
+            if let Err(err) = res {
+                app.msgs.push(format!("Couldn't write to the state log: {err}").into());
+            }
+        }
+
+        app.msgs.push(summary.into());
+    }
+
+    /// Shows the codec, sample rate and channels of the playing file, for finding out why a file
+    /// sounds wrong
+    pub fn show_audio_info(app: &mut App) {
+        match app.player.audio_info() {
+            Some(info) => app.msgs.push(format!("Audio: {info}").into()),
+            None => app.msgs.push("No audio stream is loaded".into()),
+        }
+    }
+
+    /// Runs a `command` received through the socket
+    pub fn run_socket_command(app: &mut App, mediainfo: &mut MediaInfo, command: Command) {
+        match command {
+            Command::Play => {
+                if app.player.is_paused() {
+                    toggle_play(app, mediainfo);
+                }
+            }
+            Command::Pause => {
+                if app.player.is_playing() {
+                    toggle_play(app, mediainfo);
+                }
+            }
+            Command::Next => {
+                let _ = next_chapter(app, mediainfo, true);
+            }
+            Command::Seek(secs) => {
+                let current_chapter = app.get_current_chapter(mediainfo);
+                if secs > current_chapter.length {
+                    app.msgs.push("Given position is bigger than the length of the chapter".into());
+                    return;
+                }
+
+                let pos = current_chapter.get_start_position() + secs;
+                if app.seek_in_chapter(pos, mediainfo).is_err() {
+                    app.msgs.push(format!("Couldn't move to {pos}").into());
+                }
+            }
+        }
+    }
+
+    /// Writes a one-line status of the playback to the file given with `--status-file`
+    pub fn write_status_file(app: &mut App, mediainfo: &MediaInfo) {
+        let path = match &app.status_file {
+            Some(path) => path,
+            None => return,
+        };
+
+        let current_chapter = app.get_current_chapter(mediainfo);
+        let state = if app.book_ended_at.is_some() {
+            "⏹"
+        } else if app.player.is_playing() {
+            "▶"
+        } else {
+            "⏸"
+        };
+        let status = format!(
+            "{} {} {}/{}\n",
+            state,
+            current_chapter.get_name(),
+            app.cache.formmated_now.as_deref().unwrap_or("0s"),
+            current_chapter.length_display
+        );
+
+        if let Err(err) = std::fs::write(path, status) {
+            app.msgs.push(format!("Couldn't write the status file: {err}").into());
         }
     }
 
@@ -638,7 +1709,8 @@ mod actions {
         mediainfo: &mut MediaInfo,
         should_update: bool,
     ) -> ControlFlow<()> {
-        if app.current_chapter_index + 1 >= mediainfo.chaptercount {
+        let wraps = app.current_chapter_index + 1 >= mediainfo.chaptercount();
+        if wraps && !app.wrap_around {
             app.msgs.push("You are the end of the playlist. Can't move any further.".into());
             return ControlFlow::Continue(());
         }
@@ -651,18 +1723,136 @@ mod actions {
             }
         }
         let was_playing = app.player.is_playing_and_pause();
-        app.load_chapter(app.current_chapter_index + 1, &*mediainfo);
+        let index = if wraps { 0 } else { app.current_chapter_index + 1 };
+        app.load_chapter(index, &*mediainfo);
         if was_playing {
             app.player.play();
         }
-        app.marked_position = None;
-        app.msgs.push("Moved to the next chapter".into());
+        mediainfo.marked_position = None;
+        if wraps {
+            app.msgs.push("Wrapped around to the first chapter".into());
+        } else {
+            app.msgs.push("Moved to the next chapter".into());
+        }
         ControlFlow::Continue(())
     }
 
+    /// Moves to the first chapter of the playlist
+    pub fn first_chapter(app: &mut App, mediainfo: &mut MediaInfo) {
+        if app.current_chapter_index == 0 {
+            app.msgs.push("You are already at the first chapter.".into());
+            return;
+        }
+        move_to_chapter(app, mediainfo, 0);
+        app.msgs.push("Moved to the first chapter".into());
+    }
+
+    /// Moves to the last chapter of the playlist
+    pub fn last_chapter(app: &mut App, mediainfo: &mut MediaInfo) {
+        let last = mediainfo.chaptercount() - 1;
+        if app.current_chapter_index == last {
+            app.msgs.push("You are already at the last chapter.".into());
+            return;
+        }
+        move_to_chapter(app, mediainfo, last);
+        app.msgs.push("Moved to the last chapter".into());
+    }
+
+    /// Saves the position of the current chapter and loads the one under `index`, keeping it
+    /// playing if it was
+    fn move_to_chapter(app: &mut App, mediainfo: &mut MediaInfo, index: usize) {
+        if let Some(pos) = app.player.get_position_sec() {
+            app.get_mut_current_chapter(mediainfo).update_last_position(pos);
+        }
+        let was_playing = app.player.is_playing_and_pause();
+        app.load_chapter(index, &*mediainfo);
+        if was_playing {
+            app.player.play();
+        }
+        mediainfo.marked_position = None;
+    }
+
+    /// Asks for a chapter number, counted from 1, or for a track number with `by_track` (within
+    /// the file for m4a/m4b) and moves playlist and playback to that chapter. The position before
+    /// the jump can be restored with ','
+    pub fn go_to_chapter<B: Backend>(
+        app: &mut App,
+        mediainfo: &mut MediaInfo,
+        terminal: &mut Terminal<B>,
+        last_tick: &mut Instant,
+        tick_rate: Duration,
+        by_track: bool,
+    ) -> io::Result<()> {
+        let prompt = if by_track {
+            "Input the track number of the chapter"
+        } else {
+            "Input the number of the chapter"
+        };
+        let input = match ui::popouts::input::run(
+            terminal, app, mediainfo, last_tick, tick_rate, prompt, None,
+        )? {
+            Some(input) => input,
+            None => {
+                app.msgs.push("Cancelled going to a chapter".into());
+                return Ok(());
+            }
+        };
+
+        let index = if by_track {
+            let number = match input.trim().parse::<u32>() {
+                Ok(number) => number,
+                Err(_) => {
+                    app.msgs.push(format!("Invalid track number {}", input.trim()).into());
+                    return Ok(());
+                }
+            };
+            match mediainfo.chapter_by_track_number(app.current_chapter_index, number) {
+                Some(index) => index,
+                None => {
+                    app.msgs.push(format!("No chapter with the track number {number}").into());
+                    return Ok(());
+                }
+            }
+        } else {
+            match input.trim().parse::<usize>() {
+                Ok(number) if (1..=mediainfo.chaptercount()).contains(&number) => number - 1,
+                _ => {
+                    app.msgs.push(
+                        format!(
+                            "Invalid chapter number, expected 1 to {}",
+                            mediainfo.chaptercount()
+                        )
+                        .into(),
+                    );
+                    return Ok(());
+                }
+            }
+        };
+
+        let pos = match app.player.get_position_sec() {
+            Some(pos) => pos,
+            None => {
+                app.msgs.push("Couldn't get the position".into());
+                return Ok(());
+            }
+        };
+        app.get_mut_current_chapter(mediainfo).update_last_position(pos);
+        app.pos_and_chap_before_jump = Some((pos, app.current_chapter_index));
+
+        let was_playing = app.player.is_playing_and_pause();
+        app.load_chapter(index, mediainfo);
+        if was_playing {
+            app.player.play();
+        }
+        mediainfo.marked_position = None;
+        app.msgs.push(format!("Moved to chapter {}", index + 1).into());
+        Ok(())
+    }
+
     /// Moves playlist and playback to the previous chapter
     pub fn prev_chapter(app: &mut App, mediainfo: &mut MediaInfo) {
-        if app.current_chapter_index < 1 {
+        let wraps = app.current_chapter_index < 1;
+        if wraps && !app.wrap_around {
             app.msgs.push("You are the start of the playlist. Can't move any backwards.".into());
             return;
         }
@@ -673,94 +1863,736 @@ mod actions {
             return;
         }
         let was_playing = app.player.is_playing_and_pause();
-        app.load_chapter(app.current_chapter_index - 1, &*mediainfo);
+        let index =
+            if wraps { mediainfo.chaptercount() - 1 } else { app.current_chapter_index - 1 };
+        app.load_chapter(index, &*mediainfo);
         if was_playing {
             app.player.play();
         }
-        app.marked_position = None;
-        app.msgs.push("Moved to the previous chapter".into());
+        mediainfo.marked_position = None;
+        if wraps {
+            app.msgs.push("Wrapped around to the last chapter".into());
+        } else {
+            app.msgs.push("Moved to the previous chapter".into());
+        }
+    }
+
+    /// Moves the current chapter one place down or up the playlist
+    pub fn move_chapter(app: &mut App, mediainfo: &mut MediaInfo, down: bool) {
+        let index = app.current_chapter_index;
+        let new_index = if down {
+            if index + 1 >= mediainfo.chaptercount() {
+                app.msgs.push("The chapter is already at the end of the playlist.".into());
+                return;
+            }
+            index + 1
+        } else {
+            if index == 0 {
+                app.msgs.push("The chapter is already at the start of the playlist.".into());
+                return;
+            }
+            index - 1
+        };
+
+        mediainfo.chapters.swap(index, new_index);
+        mediainfo.is_custom_order = true;
+        app.current_chapter_index = new_index;
+        // the loop, the last bookmark and the deleted bookmarks follow their chapters to the new
+        // places
+        let swapped = |it: usize| {
+            if it == index {
+                new_index
+            } else if it == new_index {
+                index
+            } else {
+                it
+            }
+        };
+        if let Some((chapter_index, a, b)) = app.ab_loop {
+            app.ab_loop = Some((swapped(chapter_index), a, b));
+        }
+        if let Some((chapter_index, position)) = app.last_bookmark {
+            app.last_bookmark = Some((swapped(chapter_index), position));
+        }
+        if let Some((chapter_index, _)) = app.deleted_bookmarks.as_mut() {
+            *chapter_index = swapped(*chapter_index);
+        }
+        app.pos_and_chap_before_jump = None;
+        app.index_all_bookmark = None;
+        app.ui.view_order = None;
+        app.cache.invalidate_pls();
+        app.msgs.push(format!("Moved the chapter to position {}", new_index + 1).into());
+    }
+
+    /// Resets the progress of every chapter after a confirmation and starts the book from the
+    /// first chapter
+    pub fn restart_book<B: Backend>(
+        app: &mut App,
+        mediainfo: &mut MediaInfo,
+        terminal: &mut Terminal<B>,
+        last_tick: &mut Instant,
+        tick_rate: Duration,
+    ) -> io::Result<()> {
+        let res = ui::popouts::yn::run(
+            terminal,
+            app,
+            mediainfo,
+            last_tick,
+            tick_rate,
+            "Are you sure you want to reset the progress of the whole book? y/n",
+        )?;
+        if !res {
+            app.msgs.push("Cancelled restarting the book".into());
+            return Ok(());
+        }
+
+        let was_playing = app.player.is_playing_and_pause();
+        mediainfo.reset_progress();
+        app.pos_and_chap_before_jump = None;
+        app.load_chapter(0, mediainfo);
+        app.cache.invalidate_pls();
+        if was_playing {
+            app.player.play();
+        }
+
+        app.msgs.push("Restarted the book from the first chapter".into());
+        if app.fresh {
+            app.msgs.push("Not saving in --fresh mode".into());
+        } else if let Err(err) = mediainfo.save_to_file() {
+            app.msgs.push_error(format!("Failed to save the file with err {err}").into());
+        }
+
+        Ok(())
+    }
+
+    /// Removes the current chapter from the playlist after a confirmation and saves the change
+    pub fn remove_chapter<B: Backend>(
+        app: &mut App,
+        mediainfo: &mut MediaInfo,
+        terminal: &mut Terminal<B>,
+        last_tick: &mut Instant,
+        tick_rate: Duration,
+    ) -> io::Result<()> {
+        if mediainfo.chaptercount() <= 1 {
+            app.msgs.push("Can't remove the only chapter of the playlist.".into());
+            return Ok(());
+        }
+
+        let res = ui::popouts::yn::run(
+            terminal,
+            app,
+            mediainfo,
+            last_tick,
+            tick_rate,
+            "Are you sure you want to remove the current chapter from the playlist? y/n",
+        )?;
+        if !res {
+            app.msgs.push("Cancelled removing the chapter".into());
+            return Ok(());
+        }
+
+        let was_playing = app.player.is_playing_and_pause();
+        app.player.flush_position(app.get_mut_current_chapter(mediainfo));
+
+        let removed_index = app.current_chapter_index;
+        let removed = mediainfo.chapters.remove(removed_index);
+        // remember the file so it doesn't get added back on the next start, unless other m4a/m4b
+        // chapters of it are still in the playlist
+        if !mediainfo.chapters.iter().any(|it| it.filename == removed.filename) {
+            mediainfo.removed_files.push(removed.filename.clone());
+        }
+
+        let index = std::cmp::min(app.current_chapter_index, mediainfo.chaptercount() - 1);
+        mediainfo.last_chapter = index;
+        app.pos_and_chap_before_jump = None;
+        // the loop was in the removed chapter, the last bookmark and the deleted bookmarks move up
+        // with their chapters
+        app.ab_loop = None;
+        app.last_bookmark = match app.last_bookmark {
+            Some((chapter_index, _)) if chapter_index == removed_index => None,
+            Some((chapter_index, position)) if chapter_index > removed_index => {
+                Some((chapter_index - 1, position))
+            }
+            other => other,
+        };
+        app.deleted_bookmarks = match app.deleted_bookmarks.take() {
+            Some((chapter_index, _)) if chapter_index == removed_index => None,
+            Some((chapter_index, deleted)) if chapter_index > removed_index => {
+                Some((chapter_index - 1, deleted))
+            }
+            other => other,
+        };
+        app.index_all_bookmark = None;
+        app.ui.view_order = None;
+        app.load_chapter(index, mediainfo);
+        if was_playing {
+            app.player.play();
+        }
+
+        app.msgs.push(format!("Removed {} from the playlist", removed.get_name()).into());
+        if app.fresh {
+            app.msgs.push("Not saving in --fresh mode".into());
+        } else if let Err(err) = mediainfo.save_to_file() {
+            app.msgs.push_error(format!("Failed to save the file with err {err}").into());
+        }
+
+        Ok(())
+    }
+
+    /// Toggles showing filenames instead of titles in the playlist
+    pub fn toggle_filenames(app: &mut App) {
+        app.ui.show_filenames = !app.ui.show_filenames;
+        app.cache.pl_titles = None;
+        if app.ui.show_filenames {
+            app.msgs.push("Showing filenames in the playlist".into());
+        } else {
+            app.msgs.push("Showing titles in the playlist".into());
+        }
+    }
+
+    /// Toggles starting the next chapter when the current one ends
+    pub fn toggle_auto_advance(app: &mut App) {
+        app.auto_advance = !app.auto_advance;
+        if app.auto_advance {
+            app.msgs.push("The next chapter starts when the current one ends".into());
+        } else {
+            app.msgs.push("The playback stops when the current chapter ends".into());
+        }
+    }
+
+    /// Toggles showing how long the current listening session lasts
+    pub fn toggle_session_time(app: &mut App) {
+        app.ui.show_session = !app.ui.show_session;
+        if app.ui.show_session {
+            app.msgs.push("Showing the session time".into());
+        } else {
+            app.msgs.push("Hiding the session time".into());
+        }
+    }
+
+    /// Toggles between the compact and detailed info panel
+    pub fn toggle_compact_info(app: &mut App) {
+        app.ui.compact_info = !app.ui.compact_info;
+        if app.ui.compact_info {
+            app.msgs.push("Showing the compact info panel".into());
+        } else {
+            app.msgs.push("Showing the detailed info panel".into());
+        }
+    }
+
+    /// Toggles showing the playlist sorted by completion, least completed first. Only the
+    /// displayed order changes, the order of the chapters stays the same
+    pub fn toggle_completion_order(app: &mut App, mediainfo: &MediaInfo) {
+        if app.ui.view_order.take().is_some() {
+            app.msgs.push("Showing the playlist in its natural order".into());
+        } else {
+            let mut order: Vec<usize> = (0..mediainfo.chaptercount()).collect();
+            order.sort_by(|&a, &b| {
+                mediainfo.chapters[a].completion().total_cmp(&mediainfo.chapters[b].completion())
+            });
+            app.ui.view_order = Some(order);
+            app.msgs.push("Showing the playlist sorted by completion".into());
+        }
+        app.cache.invalidate_pls();
+    }
+
+    /// Toggles the speed and volume override of the current chapter. Pins the current speed and
+    /// volume to the chapter or, if it already has an override, goes back to the ones of the book
+    pub fn toggle_chapter_override(app: &mut App, mediainfo: &mut MediaInfo) {
+        let index = app.current_chapter_index;
+        let chapter = &mediainfo.chapters[index];
+        if chapter.speed_override.is_some() || chapter.volume_override.is_some() {
+            let chapter = &mut mediainfo.chapters[index];
+            chapter.speed_override = None;
+            chapter.volume_override = None;
+            if let Err(err) = app.player.set_speed(mediainfo.speed) {
+                app.msgs.push(format!("Failed to set the speed: {err}").into());
+            }
+            app.player.set_volume(mediainfo.volume);
+            app.msgs.push("Using the speed and volume of the book for this chapter".into());
+        } else {
+            let speed = mediainfo.speed_at(index);
+            let volume = mediainfo.volume_at(index);
+            let chapter = &mut mediainfo.chapters[index];
+            chapter.speed_override = Some(speed);
+            chapter.volume_override = Some(volume);
+            app.msgs.push(
+                format!(
+                    "Keeping speed {speed} and volume {}% for this chapter",
+                    (volume * 100.0).round()
+                )
+                .into(),
+            );
+        }
+        app.cache.invalidate_pls();
+    }
+
+    /// Returns true for keys of the main view that change the book or the config, they are
+    /// disabled with `--readonly`
+    pub fn is_editing_key(key: &KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char(c) if key.modifiers == KeyModifiers::CONTROL => "poebn".contains(c),
+            KeyCode::Char(c) => "aAmMdDXTJKzrFUE".contains(c),
+            _ => false,
+        }
+    }
+
+    /// Asks for a speed used only for the current chapter, the speed of the book stays the same
+    pub fn set_chapter_speed<B: Backend>(
+        app: &mut App,
+        mediainfo: &mut MediaInfo,
+        terminal: &mut Terminal<B>,
+        last_tick: &mut Instant,
+        tick_rate: Duration,
+    ) -> io::Result<()> {
+        let input = match ui::popouts::input::run(
+            terminal,
+            app,
+            mediainfo,
+            last_tick,
+            tick_rate,
+            "Input speed for this chapter only. Bigger than 0.0",
+            None,
+        )? {
+            Some(input) => input,
+            None => {
+                app.msgs.push("Cancelled setting the speed of the chapter".into());
+                return Ok(());
+            }
+        };
+
+        let speed = match input.trim().parse::<f64>() {
+            Ok(speed) if speed > 0.0 => speed,
+            _ => {
+                app.msgs.push("Invalid input".into());
+                return Ok(());
+            }
+        };
+
+        if app.player.set_speed(speed).is_err() {
+            app.msgs.push("Couldn't set the speed".into());
+            return Ok(());
+        }
+        app.get_mut_current_chapter(mediainfo).speed_override = Some(speed);
+        app.cache.invalidate_pls();
+        app.msgs.push(format!("Speed {speed} set for this chapter").into());
+        Ok(())
+    }
+
+    /// Asks whether to resume where the book was left off or to start over from the first chapter.
+    /// Doesn't ask if the book wasn't played yet
+    pub fn ask_resume<B: Backend>(
+        app: &mut App,
+        mediainfo: &MediaInfo,
+        terminal: &mut Terminal<B>,
+        last_tick: &mut Instant,
+        tick_rate: Duration,
+    ) -> io::Result<()> {
+        let current_chapter = app.get_current_chapter(mediainfo);
+        let start_position = current_chapter.get_start_position();
+        let position = current_chapter.last_position.saturating_sub(start_position);
+        if app.current_chapter_index == 0 && position == 0 {
+            return Ok(());
+        }
+
+        let prompt = format!(
+            "Resume at {} ({})? y - resume, n - start over",
+            current_chapter.get_name(),
+            formatted_time(position)
+        );
+        if ui::popouts::yn::run(terminal, app, mediainfo, last_tick, tick_rate, &prompt)? {
+            return Ok(());
+        }
+
+        app.load_chapter(0, mediainfo);
+        let start_position = app.get_current_chapter(mediainfo).get_start_position();
+        if app.seek_in_chapter(start_position, mediainfo).is_err() {
+            app.msgs.push("Couldn't move to the start of the book".into());
+        } else {
+            app.msgs.push("Starting over from the first chapter".into());
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the player to play through `sink` (or `autoaudiosink` if it's `None`), keeping the
+    /// position and the state of the playback
+    pub fn switch_audio_sink(app: &mut App, mediainfo: &MediaInfo, sink: Option<AudioSink>) {
+        let name = sink.as_ref().map_or_else(|| "autoaudiosink".to_string(), AudioSink::name);
+        if let Some(sink) = &sink {
+            if !sink.is_available() {
+                app.msgs.push(format!("Couldn't create the {name} sink").into());
+                return;
+            }
+        }
+
+        let was_playing = app.player.is_playing();
+        let position = app.player.get_position_sec();
+
+        app.audio_sink = sink;
+        app.player.null();
+        app.player = Player::new(app.audio_sink.as_ref());
+        app.player.load_chapter(
+            app.get_current_chapter(mediainfo),
+            &mediainfo.path,
+            mediainfo.speed_at(app.current_chapter_index),
+            mediainfo.volume_at(app.current_chapter_index),
+            0,
+        );
+        if let Some(position) = position {
+            if app.seek_in_chapter(position, mediainfo).is_err() {
+                app.msgs.push("Couldn't restore the position".into());
+            }
+        }
+        if was_playing {
+            app.player.play();
+        }
+
+        app.msgs.push(format!("Playing through {name}").into());
+    }
+
+    /// Saves the speed and volume of the current book as the defaults for new books
+    pub fn save_default_speed_and_volume(app: &mut App, mediainfo: &MediaInfo) {
+        // a config that couldn't be parsed would be overwritten with the defaults
+        let config = match Config::load() {
+            Ok(config) => Config { speed: mediainfo.speed, volume: mediainfo.volume, ..config },
+            Err(err) => {
+                app.msgs.push_error(format!("{err}, not saving the defaults").into());
+                return;
+            }
+        };
+        match config.save() {
+            Ok(()) => app.msgs.push(
+                format!(
+                    "Saved speed {} and volume {:.0}% as the defaults for new books",
+                    config.speed,
+                    config.volume * 100.0
+                )
+                .into(),
+            ),
+            Err(err) => app.msgs.push(format!("Failed to save the config with err {err}").into()),
+        }
+    }
+
+    /// Trims leading and trailing whitespace from the m4a/m4b titles and descriptions of all
+    /// chapters, the ones saved in `gadacz_data.json`
+    pub fn trim_titles(app: &mut App, mediainfo: &mut MediaInfo) {
+        let mut changed = 0;
+        for chapter in mediainfo.chapters.iter_mut() {
+            // titles from the tags are already trimmed when they are read
+            let fields = [&mut chapter.m4_title, &mut chapter.description];
+            for text in fields.into_iter().flatten() {
+                let trimmed = text.trim();
+                if trimmed.len() != text.len() {
+                    *text = trimmed.to_owned();
+                    changed += 1;
+                }
+            }
+        }
+
+        app.cache.invalidate_all();
+        app.msgs.push(format!("Trimmed whitespace in {changed} titles and descriptions").into());
+    }
+
+    /// Recenters the playlist on the currently playing chapter and redraws it
+    pub fn recenter_playlist(app: &mut App) {
+        app.cache.invalidate_pls();
+        app.msgs.push("Recentered the playlist on the current chapter".into());
+    }
+
+    /// Raises the volume by `app.volume_step`, also bound to scrolling up
+    pub fn increase_volume(app: &mut App, mediainfo: &mut MediaInfo) {
+        let volume = mediainfo.volume_at(app.current_chapter_index);
+        let step = app.volume_step;
+        if volume + step > 1.0 {
+            mediainfo.set_volume_at(app.current_chapter_index, 1.0);
+            app.player.set_volume(1.0);
+            app.msgs.push("Can't increase volume beyond 100%".into());
+        } else {
+            mediainfo.set_volume_at(app.current_chapter_index, volume + step);
+            app.player.set_volume(volume + step);
+            app.msgs.push(format!("Increased volume by {}%", (step * 100.0).round()).into());
+        }
+    }
+
+    /// Lowers the volume by `app.volume_step`, also bound to scrolling down
+    pub fn descrease_volume(app: &mut App, mediainfo: &mut MediaInfo) {
+        let volume = mediainfo.volume_at(app.current_chapter_index);
+        let step = app.volume_step;
+        if volume - step < 0.0 {
+            mediainfo.set_volume_at(app.current_chapter_index, 0.0);
+            app.player.set_volume(0.0);
+            app.msgs.push("Can't descrease volume below 0%".into());
+        } else {
+            mediainfo.set_volume_at(app.current_chapter_index, volume - step);
+            app.player.set_volume(volume - step);
+            app.msgs.push(format!("Decreased volume by {}%", (step * 100.0).round()).into());
+        }
+    }
+
+    /// Saves the current position of the chapter and index of currently played chapter then breaks
+    /// the loop
+    pub fn quit(app: &mut App, mediainfo: &mut MediaInfo) -> ControlFlow<()> {
+        if let Some(pos) = app.player.get_position_sec() {
+            app.get_mut_current_chapter(mediainfo).update_last_position(pos);
+        } else {
+            app.msgs.push("Couldn't get the position".into());
+            return ControlFlow::Continue(());
+        }
+        mediainfo.last_chapter = app.current_chapter_index;
+        app.inhibitor.release();
+        ControlFlow::Break(())
+    }
+
+    /// Add a description for the currently chosen item in the playlist
+    pub fn add_description<B: Backend>(
+        app: &mut App,
+        mediainfo: &mut MediaInfo,
+        terminal: &mut Terminal<B>,
+        last_tick: &mut Instant,
+        tick_rate: Duration,
+    ) -> io::Result<()> {
+        let was_playing = app.player.is_playing_and_pause();
+        app.player.flush_position(app.get_mut_current_chapter(mediainfo));
+
+        if let Some(description) = ui::popouts::input::run(
+            terminal,
+            app,
+            mediainfo,
+            last_tick,
+            tick_rate,
+            "Input a description for the chapter",
+            app.get_current_chapter(mediainfo).description.as_ref(),
+        )? {
+            let current_chapter = app.get_mut_current_chapter(mediainfo);
+
+            if description.is_empty() {
+                current_chapter.description = None;
+            } else {
+                current_chapter.description = Some(description);
+            }
+        } else {
+            app.msgs.push("Cancelled adding a description".into());
+        };
+
+        if was_playing {
+            app.player.play();
+        }
+        app.cache.pl_titles = None;
+
+        Ok(())
+    }
+
+    /// Asks for a note for the whole book, an empty one removes it
+    pub fn edit_book_note<B: Backend>(
+        app: &mut App,
+        mediainfo: &mut MediaInfo,
+        terminal: &mut Terminal<B>,
+        last_tick: &mut Instant,
+        tick_rate: Duration,
+    ) -> io::Result<()> {
+        let was_playing = app.player.is_playing_and_pause();
+        app.player.flush_position(app.get_mut_current_chapter(mediainfo));
+
+        if let Some(note) = ui::popouts::input::run(
+            terminal,
+            app,
+            mediainfo,
+            last_tick,
+            tick_rate,
+            "Input a note for the book",
+            mediainfo.book_note.as_ref(),
+        )? {
+            if note.is_empty() {
+                mediainfo.book_note = None;
+                app.msgs.push("Removed the note for the book".into());
+            } else {
+                mediainfo.book_note = Some(note);
+                app.msgs.push("Saved the note for the book".into());
+            }
+        } else {
+            app.msgs.push("Cancelled editing the note".into());
+        };
+
+        if was_playing {
+            app.player.play();
+        }
+
+        Ok(())
+    }
+
+    /// Shows the note for the whole book in a popout
+    pub fn show_book_note<B: Backend>(
+        app: &mut App,
+        mediainfo: &MediaInfo,
+        terminal: &mut Terminal<B>,
+        last_tick: &mut Instant,
+        tick_rate: Duration,
+    ) -> io::Result<()> {
+        match &mediainfo.book_note {
+            Some(note) => ui::popouts::info::run(
+                terminal,
+                app,
+                mediainfo,
+                last_tick,
+                tick_rate,
+                "Note for the book",
+                note,
+            ),
+            None => {
+                app.msgs.push("There is no note for the book, C-n adds one".into());
+                Ok(())
+            }
+        }
+    }
+
+    /// Asks for minutes after which the playback gets paused. If the sleep timer is already set
+    /// the minutes are added to it, 0 turns it off
+    pub fn set_sleep_timer<B: Backend>(
+        app: &mut App,
+        mediainfo: &MediaInfo,
+        terminal: &mut Terminal<B>,
+        last_tick: &mut Instant,
+        tick_rate: Duration,
+    ) -> io::Result<()> {
+        let prompt = if app.sleep_deadline.is_some() {
+            "Input minutes to add to the sleep timer. 0 turns it off"
+        } else {
+            "Input minutes until pausing"
+        };
+        let input = match ui::popouts::input::run(
+            terminal, app, mediainfo, last_tick, tick_rate, prompt, None,
+        )? {
+            Some(input) => input,
+            None => {
+                app.msgs.push("Cancelled setting the sleep timer".into());
+                return Ok(());
+            }
+        };
+
+        match input.trim().parse::<u64>() {
+            Ok(0) => cancel_sleep_timer(app),
+            Ok(minutes) => {
+                let added = Duration::from_secs(minutes * 60);
+                let deadline = match app.sleep_deadline {
+                    Some(deadline) => deadline + added,
+                    None => Instant::now() + added,
+                };
+                app.sleep_deadline = Some(deadline);
+                let remaining = app.sleep_remaining();
+                app.cache.update_sleep_countdown(remaining);
+                app.msgs
+                    .push(format!("Pausing in {}", formatted_time(remaining.unwrap_or(0))).into());
+            }
+            Err(_) => app.msgs.push("Invalid input".into()),
+        }
+        Ok(())
+    }
+
+    /// Writes the descriptions and bookmarks of the book to `notes.md`
+    pub fn export_notes(app: &mut App, mediainfo: &mut MediaInfo) {
+        match mediainfo.export_notes() {
+            Ok(file) => app.msgs.push(format!("Exported the notes to {}", file.display()).into()),
+            Err(err) => app.msgs.push(format!("Couldn't export the notes: {err}").into()),
+        }
     }
 
-    pub fn increase_volume(app: &mut App, mediainfo: &mut MediaInfo) {
-        if mediainfo.volume + 0.05 > 1.0 {
-            mediainfo.volume = 1.0;
-            app.player.set_volume(1.0);
-            app.msgs.push("Can't increase volume beyond 100%".into());
+    /// Clears the sleep timer, unlike entering more minutes
+    pub fn cancel_sleep_timer(app: &mut App) {
+        if app.sleep_deadline.take().is_some() {
+            app.cache.update_sleep_countdown(None);
+            app.msgs.push("Sleep timer cancelled.".into());
         } else {
-            mediainfo.volume += 0.05;
-            app.player.set_volume(mediainfo.volume);
-            app.msgs.push("Increased volume by 5%".into());
+            app.msgs.push("There is no sleep timer set".into());
         }
     }
 
-    pub fn descrease_volume(app: &mut App, mediainfo: &mut MediaInfo) {
-        if mediainfo.volume - 0.05 < 0.0 {
-            mediainfo.volume = 0.0;
-            app.player.set_volume(0.0);
-            app.msgs.push("Can't descrease volume below 0%".into());
-        } else {
-            mediainfo.volume -= 0.05;
-            app.player.set_volume(mediainfo.volume);
-            app.msgs.push("Decreased volume by 5%".into());
+    /// Starts ramping the speed up to the skim speed, or keeps it up if it's already going. The
+    /// terminal doesn't report releasing keys so skimming ends shortly after the presses stop
+    pub fn skim(app: &mut App) {
+        match app.skim.as_mut() {
+            Some(skim) => skim.hold(),
+            None => {
+                app.skim = Some(Skim::start());
+                app.msgs.push("Skimming, hold w to keep going".into());
+            }
         }
     }
 
-    /// Saves the current position of the chapter and index of currently played chapter then breaks
-    /// the loop
-    pub fn quit(app: &mut App, mediainfo: &mut MediaInfo) -> ControlFlow<()> {
-        if let Some(pos) = app.player.get_position_sec() {
-            app.get_mut_current_chapter(mediainfo).update_last_position(pos);
-        } else {
-            app.msgs.push("Couldn't get the position".into());
-            return ControlFlow::Continue(());
+    /// Sets the A point of the loop, then the B point, the third press clears the loop
+    pub fn set_ab_point(app: &mut App, mediainfo: &MediaInfo) {
+        let pos = match app.player.get_position_sec() {
+            Some(pos) => pos,
+            None => {
+                app.msgs.push("Couldn't get the position".into());
+                return;
+            }
+        };
+        let start_position = app.get_current_chapter(mediainfo).start_position;
+
+        match app.ab_loop {
+            None => {
+                app.ab_loop = Some((app.current_chapter_index, pos, None));
+                app.msgs.push(
+                    format!(
+                        "Set the A point at {}",
+                        crate::helpers::format_position(pos, start_position)
+                    )
+                    .into(),
+                );
+            }
+            Some((_, a, None)) if pos <= a => {
+                app.msgs.push("The B point has to be after the A point".into());
+            }
+            Some((chapter_index, a, None)) => {
+                app.ab_loop = Some((chapter_index, a, Some(pos)));
+                app.msgs.push(
+                    format!(
+                        "Set the B point at {}",
+                        crate::helpers::format_position(pos, start_position)
+                    )
+                    .into(),
+                );
+            }
+            Some((_, _, Some(_))) => {
+                app.ab_loop = None;
+                app.msgs.push("Cleared the A-B loop".into());
+            }
         }
-        mediainfo.last_chapter = app.current_chapter_index;
-        ControlFlow::Break(())
     }
 
-    /// Add a description for the currently chosen item in the playlist
-    pub fn add_description<B: Backend>(
+    /// Deletes the description of the current chapter, asking for a confirmation first if the
+    /// config says so
+    pub fn delete_description<B: Backend>(
         app: &mut App,
         mediainfo: &mut MediaInfo,
         terminal: &mut Terminal<B>,
         last_tick: &mut Instant,
         tick_rate: Duration,
     ) -> io::Result<()> {
-        let was_playing = app.player.is_playing_and_pause();
-
-        if let Some(description) = ui::popouts::input::run(
-            terminal,
-            app,
-            mediainfo,
-            last_tick,
-            tick_rate,
-            "Input a description for the chapter",
-            app.get_current_chapter(mediainfo).description.as_ref(),
-            35,
-        )? {
-            let current_chapter = app.get_mut_current_chapter(mediainfo);
-
-            if description.is_empty() {
-                current_chapter.description = None;
-            } else {
-                current_chapter.description = Some(description);
-            }
-        } else {
-            app.msgs.push("Cancelled adding a description".into());
-        };
-
-        if was_playing {
-            app.player.play();
+        if app.get_current_chapter(mediainfo).description.is_none() {
+            app.msgs.push("No description to delete".into());
+            return Ok(());
         }
-        app.cache.pl_titles = None;
 
-        Ok(())
-    }
+        if app.confirm.get("delete_description", true)
+            && !ui::popouts::yn::run(
+                terminal,
+                app,
+                mediainfo,
+                last_tick,
+                tick_rate,
+                "Delete description for this chapter? y/n",
+            )?
+        {
+            app.msgs.push("Cancelled deleting the description".into());
+            return Ok(());
+        }
 
-    pub fn delete_description(app: &mut App, mediainfo: &mut MediaInfo) {
         let current_chapter = app.get_mut_current_chapter(mediainfo);
         current_chapter.description = None;
         app.cache.pl_titles = None;
+        app.msgs.push("Deleted the description".into());
+        Ok(())
     }
 
     pub fn add_bookmark<B: Backend>(
@@ -771,6 +2603,7 @@ mod actions {
         tick_rate: Duration,
     ) -> std::io::Result<ControlFlow<()>> {
         let was_playing = app.player.is_playing_and_pause();
+        app.player.flush_position(app.get_mut_current_chapter(mediainfo));
         let position = app.player.get_position_sec().unwrap();
 
         let name = if let Some(name) = ui::popouts::input::run(
@@ -781,7 +2614,6 @@ mod actions {
             tick_rate,
             "Input the name for the bookmark. Confirm with Enter. Cancel with Escape",
             None,
-            100,
         )? {
             name
         } else {
@@ -800,9 +2632,31 @@ mod actions {
         Ok(ControlFlow::Continue(()))
     }
 
-    pub fn add_mark(app: &mut App) {
+    /// Adds a bookmark at the current position named after `app.bookmark_template`
+    pub fn add_quick_bookmark(app: &mut App, mediainfo: &mut MediaInfo) {
+        let position = if let Some(pos) = app.player.get_position_sec() {
+            pos
+        } else {
+            app.msgs.push("Couldnt get the current position".into());
+            return;
+        };
+
+        let current_chapter = app.get_current_chapter(mediainfo);
+        let name = crate::helpers::expand_bookmark_template(
+            &app.bookmark_template,
+            current_chapter.get_name(),
+            position.saturating_sub(current_chapter.get_start_position()),
+            current_chapter.bookmarks.len() + 1,
+        );
+
+        app.get_mut_current_chapter(mediainfo).add_bookmark(name.clone(), position);
+        app.msgs.push(format!("Added a bookmark {name}").into());
+        app.cache.invalidate_bks();
+    }
+
+    pub fn add_mark(app: &mut App, mediainfo: &mut MediaInfo) {
         if let Some(pos) = app.player.get_position_sec() {
-            app.marked_position = Some(pos);
+            mediainfo.marked_position = Some(pos);
             app.msgs.push(format!("Marked position at {pos}").into());
         } else {
             app.msgs.push("Couldnt get the current position".into());
@@ -816,8 +2670,9 @@ mod actions {
         last_tick: &mut Instant,
         tick_rate: Duration,
     ) -> std::io::Result<()> {
-        if let Some(pos) = app.marked_position {
+        if let Some(pos) = mediainfo.marked_position {
             let was_playing = app.player.is_playing_and_pause();
+            app.player.flush_position(app.get_mut_current_chapter(mediainfo));
             let name = if let Some(name) = ui::popouts::input::run(
                 terminal,
                 app,
@@ -826,7 +2681,6 @@ mod actions {
                 tick_rate,
                 "Input the name for the bookmark. Confirm with Enter. Cancel with Escape",
                 None,
-                75,
             )? {
                 name
             } else {
@@ -837,7 +2691,7 @@ mod actions {
             app.get_mut_current_chapter(mediainfo).add_bookmark(name, pos);
             app.msgs.push("Added a bookmark".into());
             app.cache.invalidate_bks();
-            app.marked_position = None;
+            mediainfo.marked_position = None;
 
             if was_playing {
                 app.player.play();
@@ -855,16 +2709,38 @@ mod actions {
         tick_rate: Duration,
     ) -> std::io::Result<()> {
         let was_playing = app.player.is_playing_and_pause();
+        app.player.flush_position(app.get_mut_current_chapter(mediainfo));
+
+        let current_chapter = app.get_current_chapter(mediainfo);
+        let length = current_chapter.length;
+        let start_position = current_chapter.get_start_position();
+        let current = app.player.get_position_sec().unwrap_or(0).saturating_sub(start_position);
+        let preview = |input: &str| -> String {
+            // the length of a chapter the scan couldn't time is 0 until it's played
+            if input.is_empty() || length == 0 {
+                return String::new();
+            }
+            match crate::helpers::resolve_chapter_position(input, current, length) {
+                Some(secs) if secs > length => "past the end of the chapter".to_string(),
+                Some(secs) => format!(
+                    "= {} ({:.0}%)",
+                    crate::helpers::format_position(secs, None),
+                    secs as f64 / length as f64 * 100.0
+                ),
+                None => "invalid".to_string(),
+            }
+        };
 
-        let input = if let Some(name) = ui::popouts::input::run(
+        let input = if let Some(name) = ui::popouts::input::run_with_preview(
             terminal,
             app,
             mediainfo,
             last_tick,
             tick_rate,
-            "Input the position. Number followed by a 'h' - hours, 'm' - minutes, 's' - seconds",
+            "Input the position. Number followed by a 'h' - hours, 'm' - minutes, 's' - seconds. \
+             Start with '+' or '-' to move relative to the current position",
             None,
-            82,
+            Some(&preview),
         )? {
             name
         } else {
@@ -872,14 +2748,17 @@ mod actions {
             return Ok(());
         };
 
-        let secs = if let Some(secs) = crate::helpers::try_into_seconds(&input) {
-            secs
-        } else {
-            app.msgs.push(
-                "Detected an illegal character. 'h'/'m'/'s' and numbers are the only legal".into(),
-            );
-            return Ok(());
-        };
+        let secs =
+            if let Some(secs) = crate::helpers::resolve_chapter_position(&input, current, length) {
+                secs
+            } else {
+                app.msgs.push(
+                    "Detected an illegal character. 'h'/'m'/'s', numbers and a leading '+'/'-' \
+                     are the only legal"
+                        .into(),
+                );
+                return Ok(());
+            };
 
         if secs > app.get_current_chapter(mediainfo).length {
             app.msgs.push("Given position is bigger than the length of the chapter".into());
@@ -890,14 +2769,10 @@ mod actions {
             app.get_mut_current_chapter(mediainfo).before_jump_position = Some(pos);
         }
 
-        app.player
-            .seek_seconds(
-                app.get_current_chapter(mediainfo).get_start_position() + secs,
-                mediainfo.speed,
-            )
-            .unwrap();
+        let position = start_position + secs;
+        app.seek_in_chapter(position, mediainfo).unwrap();
 
-        app.msgs.push(format!("Moved to {}", input).into());
+        app.msgs.push(format!("Moved to {}", crate::helpers::format_position(secs, None)).into());
 
         if was_playing {
             app.player.play();
@@ -906,6 +2781,39 @@ mod actions {
         Ok(())
     }
 
+    /// Toggles moving to the end of the previous chapter when moving backwards past the start
+    pub fn toggle_reverse_advance(app: &mut App, mediainfo: &mut MediaInfo) {
+        mediainfo.is_reverse_advance = !mediainfo.is_reverse_advance;
+        if mediainfo.is_reverse_advance {
+            app.msgs.push("Moving backwards past the start goes to the previous chapter".into());
+        } else {
+            app.msgs.push("Moving backwards stops at the start of the chapter".into());
+        }
+    }
+
+    /// Moves `app.big_jump_secs` forwards or backwards, staying inside the chapter
+    pub fn big_jump(app: &mut App, mediainfo: &MediaInfo, forward: bool) {
+        let abs_pos = if let Some(pos) = app.player.get_position_sec() {
+            pos
+        } else {
+            app.msgs.push("Couldn't get the position".into());
+            return;
+        };
+
+        let position = if forward {
+            abs_pos + app.big_jump_secs
+        } else {
+            abs_pos.saturating_sub(app.big_jump_secs)
+        };
+        if app.seek_in_chapter(position, mediainfo).is_err() {
+            app.msgs.push("Couldn't move".into());
+            return;
+        }
+
+        let direction = if forward { "forwards" } else { "backwards" };
+        app.msgs.push(format!("Moved {} {}", formatted_time(app.big_jump_secs), direction).into());
+    }
+
     pub fn move_forward(app: &mut App, mediainfo: &mut MediaInfo) {
         let abs_pos = if let Some(pos) = app.player.get_position_sec() {
             pos
@@ -916,14 +2824,29 @@ mod actions {
         let current_chapter = app.get_current_chapter(mediainfo);
         let start_pos = current_chapter.start_position.unwrap_or(0);
         let cur_pos = abs_pos.saturating_sub(start_pos);
-        match (cur_pos + 5).cmp(&current_chapter.length) {
+        let step = mediainfo.seek_step;
+        match (cur_pos + step).cmp(&current_chapter.length) {
             std::cmp::Ordering::Equal | std::cmp::Ordering::Less => {
-                app.player.seek_seconds(abs_pos + 5, mediainfo.speed).unwrap();
-                app.msgs.push("Move forwards by 5 seconds".into());
+                app.player
+                    .seek_seconds(abs_pos + step, mediainfo.speed_at(app.current_chapter_index))
+                    .unwrap();
+                app.msgs.push(format!("Moved forwards by {step} seconds").into());
+            }
+            std::cmp::Ordering::Greater
+                if app.seek_past_end == SeekPastEnd::Advance
+                    && (app.current_chapter_index + 1 < mediainfo.chaptercount()
+                        || app.wrap_around) =>
+            {
+                let file_duration = app.player.get_duration_sec();
+                app.get_mut_current_chapter(mediainfo).mark_complete(file_duration);
+                let _ = next_chapter(app, mediainfo, false);
             }
             std::cmp::Ordering::Greater => {
                 app.player
-                    .seek_seconds(start_pos + current_chapter.length, mediainfo.speed)
+                    .seek_seconds(
+                        start_pos + current_chapter.length,
+                        mediainfo.speed_at(app.current_chapter_index),
+                    )
                     .unwrap();
                 app.msgs.push("Moved to the end".into());
             }
@@ -939,50 +2862,283 @@ mod actions {
         };
         let current_chapter = app.get_current_chapter(mediainfo);
         let start_pos = current_chapter.get_start_position();
-        if let Some(sub) = abs_pos.checked_sub(5) {
-            match sub.cmp(&start_pos) {
-                std::cmp::Ordering::Greater => {
-                    app.player.seek_seconds(sub, mediainfo.speed).unwrap();
-                    app.msgs.push("Move backwards by 5 seconds".into());
-                }
-                std::cmp::Ordering::Less | std::cmp::Ordering::Equal => {
-                    app.player.seek_seconds(start_pos, mediainfo.speed).unwrap();
-                    app.msgs.push("Moved to the start".into());
+        let step = mediainfo.seek_step;
+        match abs_pos.checked_sub(step).filter(|sub| *sub > start_pos) {
+            Some(sub) => {
+                app.player
+                    .seek_seconds(sub, mediainfo.speed_at(app.current_chapter_index))
+                    .unwrap();
+                app.msgs.push(format!("Moved backwards by {step} seconds").into());
+            }
+            None if mediainfo.is_reverse_advance && app.current_chapter_index > 0 => {
+                app.get_mut_current_chapter(mediainfo).update_last_position(abs_pos);
+
+                let was_playing = app.player.is_playing_and_pause();
+                app.load_chapter(app.current_chapter_index - 1, mediainfo);
+                let current_chapter = app.get_current_chapter(mediainfo);
+                let end = current_chapter.get_start_position()
+                    + current_chapter.length.saturating_sub(step);
+                app.player
+                    .seek_seconds(end, mediainfo.speed_at(app.current_chapter_index))
+                    .unwrap();
+                if was_playing {
+                    app.player.play();
                 }
+
+                mediainfo.marked_position = None;
+                app.msgs.push("Moved to the end of the previous chapter".into());
+            }
+            None => {
+                app.player
+                    .seek_seconds(start_pos, mediainfo.speed_at(app.current_chapter_index))
+                    .unwrap();
+                app.msgs.push("Moved to the start".into());
+            }
+        }
+    }
+
+    /// Asks for the number of seconds moved by h and l
+    pub fn set_seek_step<B: Backend>(
+        app: &mut App,
+        mediainfo: &mut MediaInfo,
+        terminal: &mut Terminal<B>,
+        last_tick: &mut Instant,
+        tick_rate: Duration,
+    ) -> io::Result<()> {
+        let input = match ui::popouts::input::run(
+            terminal,
+            app,
+            mediainfo,
+            last_tick,
+            tick_rate,
+            "Input seconds moved by h and l. Bigger than 0",
+            None,
+        )? {
+            Some(input) => input,
+            None => {
+                app.msgs.push("Cancelled setting the seek step".into());
+                return Ok(());
+            }
+        };
+
+        match input.trim().parse::<u64>() {
+            Ok(step) if step > 0 => {
+                mediainfo.seek_step = step;
+                app.msgs.push(format!("h and l now move by {step} seconds").into());
+            }
+            _ => app.msgs.push("Invalid input".into()),
+        }
+        Ok(())
+    }
+
+    /// Asks how many more times the current chapter is played before going on, 0 turns it off
+    pub fn set_repeat_count<B: Backend>(
+        app: &mut App,
+        mediainfo: &MediaInfo,
+        terminal: &mut Terminal<B>,
+        last_tick: &mut Instant,
+        tick_rate: Duration,
+    ) -> io::Result<()> {
+        let input = match ui::popouts::input::run(
+            terminal,
+            app,
+            mediainfo,
+            last_tick,
+            tick_rate,
+            "Input how many more times to play this chapter before going on. 0 turns it off",
+            None,
+        )? {
+            Some(input) => input,
+            None => {
+                app.msgs.push("Cancelled setting the repeats".into());
+                return Ok(());
+            }
+        };
+
+        match input.trim().parse::<u32>() {
+            Ok(0) => {
+                app.repeat_remaining = 0;
+                app.msgs.push("The chapter isn't repeated".into());
             }
+            Ok(count) => {
+                app.repeat_remaining = count;
+                app.msgs.push(format!("The chapter is played {count} more times").into());
+            }
+            Err(_) => app.msgs.push("Invalid input".into()),
+        }
+        Ok(())
+    }
+
+    /// Uses the current position inside the chapter as the length of the intro skipped at the
+    /// start of every chapter that wasn't played yet
+    pub fn set_intro_skip(app: &mut App, mediainfo: &mut MediaInfo) {
+        let abs_pos = if let Some(pos) = app.player.get_position_sec() {
+            pos
+        } else {
+            app.msgs.push("Couldn't get the position".into());
+            return;
+        };
+        let skip = abs_pos.saturating_sub(app.get_current_chapter(mediainfo).get_start_position());
+        mediainfo.intro_skip_secs = skip;
+
+        if skip == 0 {
+            app.msgs.push("Disabled skipping the intro".into());
         } else {
-            app.player.seek_seconds(start_pos, mediainfo.speed).unwrap();
-            app.msgs.push("Moved to the start".into());
+            app.msgs
+                .push(format!("Skipping first {} of new chapters", formatted_time(skip)).into());
         }
     }
 
-    pub fn descrease_speed(app: &mut App, mediainfo: &mut MediaInfo) {
-        let speed = ((mediainfo.speed - 0.25) * 100.0).round() / 100.0;
+    /// Decreases the speed by `step`, rounded to two decimal places
+    pub fn descrease_speed(app: &mut App, mediainfo: &mut MediaInfo, step: f64) {
+        let speed =
+            ((mediainfo.speed_at(app.current_chapter_index) - step) * 100.0).round() / 100.0;
         if speed <= 0.0 {
             app.msgs.push("Can't descrease the speed any further".into());
             return;
         }
 
         if app.player.set_speed(speed).is_ok() {
-            mediainfo.speed = speed;
+            mediainfo.set_speed_at(app.current_chapter_index, speed);
         } else {
             app.msgs.push("Couldn't descrease the speed".into());
         }
     }
 
-    pub fn increase_speed(app: &mut App, mediainfo: &mut MediaInfo) {
-        let speed = ((mediainfo.speed + 0.25) * 100.0).round() / 100.0;
+    /// Increases the speed by `step`, rounded to two decimal places
+    pub fn increase_speed(app: &mut App, mediainfo: &mut MediaInfo, step: f64) {
+        let speed =
+            ((mediainfo.speed_at(app.current_chapter_index) + step) * 100.0).round() / 100.0;
         if app.player.set_speed(speed).is_ok() {
-            mediainfo.speed = speed;
+            mediainfo.set_speed_at(app.current_chapter_index, speed);
         } else {
             app.msgs.push("Couldn't increase the speed".into());
         }
     }
 
+    /// Sets the first speed preset bigger than the current speed, going around to the first one
+    /// after the last
+    pub fn cycle_speed(app: &mut App, mediainfo: &mut MediaInfo) {
+        let current = mediainfo.speed_at(app.current_chapter_index);
+        let speed = match app.speed_presets.iter().find(|&&it| it > current + 0.001) {
+            Some(&speed) => speed,
+            None => match app.speed_presets.first() {
+                Some(&speed) => speed,
+                None => {
+                    app.msgs.push("There are no speed presets in the config".into());
+                    return;
+                }
+            },
+        };
+
+        if app.player.set_speed(speed).is_ok() {
+            mediainfo.set_speed_at(app.current_chapter_index, speed);
+            app.msgs.push(format!("Speed set to {speed}").into());
+        } else {
+            app.msgs.push("Couldn't set the speed".into());
+        }
+    }
+
+    /// Pauses and opens the file of the current chapter with `xdg-open`, or with the command in
+    /// `$GADACZ_OPEN` (its words after the first are passed before the file). The command is
+    /// detached, so the ui keeps going while it runs
+    pub fn open_current_file(app: &mut App, mediainfo: &MediaInfo) {
+        let file = mediainfo.path.join(&app.get_current_chapter(mediainfo).filename);
+        let command = std::env::var("GADACZ_OPEN")
+            .ok()
+            .filter(|it| !it.trim().is_empty())
+            .unwrap_or_else(|| "xdg-open".to_string());
+        let mut words = command.split_whitespace();
+        let program = words.next().unwrap_or("xdg-open");
+
+        if app.player.is_playing() {
+            stop_playback(app);
+        }
+
+        let child = std::process::Command::new(program)
+            .args(words)
+            .arg(&file)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+        match child {
+            Ok(mut child) => {
+                // reaped on another thread so it doesn't linger as a zombie until gadacz exits
+                std::thread::spawn(move || child.wait());
+                app.msgs.push(format!("Opened {} with {program}", file.display()).into());
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => app.msgs.push_error(
+                format!("Couldn't find {program}, set $GADACZ_OPEN to the command to use").into(),
+            ),
+            Err(err) => {
+                app.msgs.push_error(format!("Failed to run {program}: {err}").into());
+            }
+        }
+    }
+
+    /// Shows the position the info panel doesn't, the one in the file with `relative_positions`
+    /// and the one in the whole book otherwise
+    pub fn peek_position(app: &mut App, mediainfo: &MediaInfo) {
+        if app.relative_positions {
+            let position = app.cache.formatted_abs_now.as_deref().unwrap_or("unknown");
+            app.msgs.push(format!("Position in the file: {position}").into());
+            return;
+        }
+
+        let chapter = app.get_current_chapter(mediainfo);
+        let position = app
+            .player
+            .get_position_sec()
+            .map_or(0, |it| it.saturating_sub(chapter.get_start_position()));
+        let before: u64 =
+            mediainfo.chapters[..app.current_chapter_index].iter().map(|it| it.length).sum();
+        let (_, total) = mediainfo.progress();
+        app.msgs.push(
+            format!(
+                "Position in the book: {} / {}",
+                formatted_time(before + position),
+                formatted_time(total)
+            )
+            .into(),
+        );
+    }
+
+    /// Moves to the `fraction` of the current chapter, used when the progress bar is clicked
+    pub fn seek_to_fraction(app: &mut App, mediainfo: &mut MediaInfo, fraction: f64) {
+        if let Some(pos) = app.player.get_position_sec() {
+            app.get_mut_current_chapter(mediainfo).before_jump_position = Some(pos);
+        }
+
+        let chapter = app.get_current_chapter(mediainfo);
+        let offset = (fraction * chapter.length as f64) as u64;
+        let position = chapter.get_start_position() + offset;
+        if app.seek_in_chapter(position, mediainfo).is_err() {
+            app.msgs.push("Couldn't move to the clicked position".into());
+        } else {
+            app.msgs
+                .push(format!("Moved to {}", crate::helpers::format_position(offset, None)).into());
+        }
+    }
+
+    /// Goes to the start of the current chapter without resetting it like 'r' does, ':' goes back
+    pub fn jump_to_chapter_start(app: &mut App, mediainfo: &mut MediaInfo) {
+        if let Some(pos) = app.player.get_position_sec() {
+            app.get_mut_current_chapter(mediainfo).before_jump_position = Some(pos);
+        }
+
+        let start_position = app.get_current_chapter(mediainfo).get_start_position();
+        if app.seek_in_chapter(start_position, mediainfo).is_err() {
+            app.msgs.push("Couldn't go to the start of the chapter".into());
+        } else {
+            app.msgs.push("Moved to the start of the chapter".into());
+        }
+    }
+
     pub fn restore_pos_before_jump(app: &mut App, mediainfo: &mut MediaInfo) {
         app.player.if_playing_pause();
         if let Some(pos) = app.get_current_chapter(mediainfo).before_jump_position {
-            if app.player.seek_seconds(pos, mediainfo.speed).is_err() {
+            if app.seek_in_chapter(pos, mediainfo).is_err() {
                 app.msgs.push("Couldn't restore the position".into());
             } else {
                 app.msgs.push("Retored the position before a jump".into());
@@ -992,18 +3148,95 @@ mod actions {
         }
     }
 
+    /// Jumps to the next or previous bookmark of the whole book, treating the bookmarks of all
+    /// chapters as one sequence ordered by chapter and position
+    pub fn jump_to_bookmark(app: &mut App, mediainfo: &mut MediaInfo, forward: bool) {
+        let position = match app.player.get_position_sec() {
+            Some(position) => position,
+            None => {
+                app.msgs.push("Couldn't get the position".into());
+                return;
+            }
+        };
+        let chapter = app.current_chapter_index;
+        let found = if forward {
+            mediainfo.next_bookmark(chapter, position)
+        } else {
+            mediainfo.previous_bookmark(chapter, position)
+        };
+        match found {
+            Some((chapter_index, bk_index)) => {
+                jump_to(app, mediainfo, position, chapter_index, bk_index);
+            }
+            None => {
+                let direction = if forward { "after" } else { "before" };
+                app.msgs.push(format!("There are no bookmarks {direction} this position").into());
+            }
+        }
+    }
+
+    /// Jumps again to the bookmark selected last, unless it was deleted since
+    pub fn repeat_bookmark_jump(app: &mut App, mediainfo: &mut MediaInfo) {
+        let (chapter_index, bookmark_position) = match app.last_bookmark {
+            Some(last) => last,
+            None => {
+                app.msgs.push("No bookmark was selected yet".into());
+                return;
+            }
+        };
+        let bk_index = mediainfo
+            .chapters
+            .get(chapter_index)
+            .and_then(|it| it.bookmarks.iter().position(|bk| bk.position == bookmark_position));
+        let bk_index = match bk_index {
+            Some(bk_index) => bk_index,
+            None => {
+                app.last_bookmark = None;
+                app.msgs.push("The bookmark selected last was deleted".into());
+                return;
+            }
+        };
+
+        match app.player.get_position_sec() {
+            Some(position) => jump_to(app, mediainfo, position, chapter_index, bk_index),
+            None => app.msgs.push("Couldn't get the position".into()),
+        }
+    }
+
+    /// Selects a bookmark of any chapter keeping the play state, `position` is remembered so the
+    /// jump can be undone with ',' and ':'
+    fn jump_to(
+        app: &mut App,
+        mediainfo: &mut MediaInfo,
+        position: u64,
+        chapter_index: usize,
+        bk_index: usize,
+    ) {
+        let was_playing = app.player.is_playing_and_pause();
+        app.pos_and_chap_before_jump = Some((position, app.current_chapter_index));
+        if chapter_index == app.current_chapter_index {
+            app.get_mut_current_chapter(mediainfo).before_jump_position = Some(position);
+            app.bookmark_select(None, bk_index, mediainfo);
+        } else {
+            app.bookmark_select(Some(chapter_index), bk_index, mediainfo);
+        }
+        if was_playing {
+            app.player.play();
+        }
+    }
+
     pub fn restore_pos_and_chap_before_jump(app: &mut App, mediainfo: &mut MediaInfo) {
         app.player.if_playing_pause();
         if let Some((pos, chapter)) = app.pos_and_chap_before_jump {
             if chapter == app.current_chapter_index {
-                if app.player.seek_seconds(pos, mediainfo.speed).is_err() {
+                if app.seek_in_chapter(pos, mediainfo).is_err() {
                     app.msgs.push("Couldn't restore the position".into());
                 } else {
                     app.msgs.push("0".into());
                 }
             } else {
                 app.load_chapter(chapter, mediainfo);
-                if app.player.seek_seconds(pos, mediainfo.speed).is_err() {
+                if app.seek_in_chapter(pos, mediainfo).is_err() {
                     app.msgs.push("Couldn't restore the position".into());
                 } else {
                     app.msgs.push("1".into());
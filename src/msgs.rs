@@ -8,16 +8,18 @@ type Msg = Cow<'static, str>;
 /// Data related to the message bar
 pub struct Msgs {
     pub current: Option<Msg>,
+    pub is_pinned: bool, // if true the current message stays until a key is pressed
     pub last_time: Instant, // the instant at which last message was shown
     pub timeout: Duration,
-    pub queue: Vec<Msg>,   // messages to be shown
-    pub history: Vec<Msg>, // history of messages
+    pub queue: Vec<(Msg, bool)>, // messages to be shown and whether they are pinned
+    pub history: Vec<Msg>,       // history of messages
 }
 
 impl Msgs {
     pub fn default() -> Self {
         Msgs {
             current: None,
+            is_pinned: false,
             last_time: Instant::now(),
             timeout: Duration::from_secs(4),
             queue: Vec::new(),
@@ -26,11 +28,17 @@ impl Msgs {
     }
 
     pub fn on_tick(&mut self) {
-        if let Some(new_msg) = self.queue.pop() {
+        // a pinned message isn't replaced either, the queued ones wait for it to be unpinned
+        if self.is_pinned {
+            return;
+        }
+
+        if let Some((new_msg, is_pinned)) = self.queue.pop() {
             if self.current.is_some() {
                 self.history.push(std::mem::take(&mut self.current).unwrap());
             }
             self.current = Some(new_msg);
+            self.is_pinned = is_pinned;
             self.last_time = Instant::now();
         } else if self.current.is_some() && self.last_time.elapsed().cmp(&self.timeout).is_ge() {
             self.history.push(std::mem::take(&mut self.current).unwrap());
@@ -38,6 +46,40 @@ impl Msgs {
     }
 
     pub fn push(&mut self, msg: Msg) {
-        self.queue.push(msg);
+        self.queue.push((msg, false));
+    }
+
+    /// Pushes a message that isn't cleared after the timeout but stays until a key is pressed,
+    /// meant for errors too long to be read in time
+    pub fn push_error(&mut self, msg: Msg) {
+        self.queue.push((msg, true));
+    }
+
+    /// Lets the pinned message be cleared, called on every key press
+    pub fn unpin(&mut self) {
+        self.is_pinned = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_stay_until_unpinned() {
+        let mut msgs = Msgs::default();
+        msgs.timeout = Duration::ZERO;
+
+        msgs.push_error("error".into());
+        msgs.on_tick();
+        msgs.push("other".into());
+        msgs.on_tick();
+        assert_eq!(msgs.current.as_deref(), Some("error"));
+
+        msgs.unpin();
+        msgs.on_tick();
+        assert_eq!(msgs.current.as_deref(), Some("other"));
+        msgs.on_tick();
+        assert_eq!(msgs.current, None);
     }
 }
@@ -1,5 +1,8 @@
 use std::path::Path;
-use std::time::Duration;
+use std::sync::{Arc,
+                Mutex};
+use std::time::{Duration,
+                Instant};
 
 use glib::BoolError;
 use gst::event::Seek;
@@ -13,6 +16,126 @@ pub struct Player {
     pub state: Option<gst::State>,
     pub bus: gst::Bus,
     // pub uri: Option<String>,
+    next_uri: Arc<Mutex<Option<String>>>, // played right after the current file, see `queue_next`
+}
+
+/// How long fading in or out takes
+const FADE_DURATION: Duration = Duration::from_millis(300);
+
+/// Volume fade in or out done over a few ticks
+pub struct Fade {
+    pub start: Instant,
+    pub is_out: bool, // if true volume goes down to 0 and the playback gets paused at the end
+}
+
+impl Fade {
+    pub fn new(is_out: bool) -> Self {
+        Self { start: Instant::now(), is_out }
+    }
+
+    /// Returns how far along the fade is, from 0.0 to 1.0
+    pub fn progress(&self) -> f64 {
+        (self.start.elapsed().as_secs_f64() / FADE_DURATION.as_secs_f64()).min(1.0)
+    }
+}
+
+/// How long the speed takes to ramp up to the skim speed and back down
+const RAMP_DURATION: Duration = Duration::from_secs(2);
+/// How long skimming goes on after the last press of its key, longer than the delay before a held
+/// key starts repeating
+const SKIM_HOLD: Duration = Duration::from_millis(600);
+/// Speed skimming ramps up to
+const SKIM_SPEED: f64 = 3.0;
+
+/// Speed ramped up to the skim speed while its key is held and back down once it's released
+pub struct Skim {
+    level: f64, // how far along the ramp the speed is, from 0.0 to 1.0
+    last_step: Instant,
+    held_until: Instant,
+}
+
+impl Skim {
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self { level: 0.0, last_step: now, held_until: now + SKIM_HOLD }
+    }
+
+    /// Keeps skimming, called on every press of the key including the repeated ones
+    pub fn hold(&mut self) {
+        self.held_until = Instant::now() + SKIM_HOLD;
+    }
+
+    /// Advances the ramp and returns the speed to play at, between `base` and the skim speed.
+    /// Returns `None` once the speed is back down at `base`
+    pub fn step(&mut self, base: f64) -> Option<f64> {
+        let now = Instant::now();
+        let delta = now.duration_since(self.last_step).as_secs_f64() / RAMP_DURATION.as_secs_f64();
+        self.last_step = now;
+        self.level =
+            if now < self.held_until { (self.level + delta).min(1.0) } else { self.level - delta };
+
+        if self.level <= 0.0 {
+            return None;
+        }
+        // eased so the change is gentle at both ends of the ramp
+        let eased = self.level * self.level * (3.0 - 2.0 * self.level);
+        Some(base + (SKIM_SPEED.max(base) - base) * eased)
+    }
+}
+
+/// Audio sink used instead of `autoaudiosink`
+#[derive(Debug, Clone)]
+pub enum AudioSink {
+    /// Element chosen with `--sink` and `--sink-device`
+    Element {
+        element: String,        // name of the element, e.g. `pulsesink` or `alsasink`
+        device: Option<String>, // value for the `device` property of the element
+    },
+    /// Output device chosen at runtime
+    Device(gst::Device),
+}
+
+impl AudioSink {
+    /// Creates the sink element, `None` if the element isn't available on this system
+    fn make(&self) -> Option<gst::Element> {
+        match self {
+            AudioSink::Element { element, device } => {
+                let sink = gst::ElementFactory::make(element).name("audiosink").build().ok()?;
+                if let Some(device) = device {
+                    // setting a property the element doesn't have panics
+                    sink.find_property("device")?;
+                    sink.set_property("device", device);
+                }
+                Some(sink)
+            }
+            AudioSink::Device(device) => device.create_element(Some("audiosink")).ok(),
+        }
+    }
+
+    /// Returns `true` if the sink element can be created
+    pub fn is_available(&self) -> bool {
+        self.make().is_some()
+    }
+
+    /// Returns a name of the sink that can be shown to the user
+    pub fn name(&self) -> String {
+        match self {
+            AudioSink::Element { element, .. } => element.clone(),
+            AudioSink::Device(device) => device.display_name().to_string(),
+        }
+    }
+}
+
+/// Lists the audio output devices available on the system
+pub fn audio_devices() -> Vec<gst::Device> {
+    let monitor = gst::DeviceMonitor::new();
+    monitor.add_filter(Some("Audio/Sink"), None);
+    if monitor.start().is_err() {
+        return Vec::new();
+    }
+    let devices = monitor.devices().into_iter().collect();
+    monitor.stop();
+    devices
 }
 
 #[derive(Debug)]
@@ -32,6 +155,12 @@ impl std::fmt::Display for Error {
 
 impl Player {
     pub fn default() -> Self {
+        Self::new(None)
+    }
+
+    /// Creates a player playing through `sink`, falls back to `autoaudiosink` if it's `None` or
+    /// it can't be created
+    pub fn new(sink: Option<&AudioSink>) -> Self {
         // let playbin = gst::ElementFactory::make_with_name("playbin", Some("gadacz")).unwrap();
         let playbin = gst::ElementFactory::make("playbin").name("gadacz").build().unwrap();
 
@@ -40,7 +169,9 @@ impl Player {
 
         // let sink = gst::ElementFactory::make_with_name("autoaudiosink",
         // Some("autoaudiosink")).unwrap();
-        let sink = gst::ElementFactory::make("autoaudiosink").name("audiosink").build().unwrap();
+        let sink = sink.and_then(AudioSink::make).unwrap_or_else(|| {
+            gst::ElementFactory::make("autoaudiosink").name("audiosink").build().unwrap()
+        });
 
         let bin = gst::Bin::new(Some("audiosink"));
         bin.add_many(&[&tempo, &sink]).unwrap();
@@ -57,11 +188,25 @@ impl Player {
 
         let bus = playbin.bus().unwrap();
 
+        // emitted from a streaming thread shortly before the current file ends, setting the uri
+        // there makes playbin go on with the next file without a gap
+        let next_uri = Arc::new(Mutex::new(None::<String>));
+        let queued = Arc::clone(&next_uri);
+        playbin.connect("about-to-finish", false, move |values| {
+            if let Some(uri) = queued.lock().unwrap().take() {
+                if let Ok(playbin) = values[0].get::<gst::Element>() {
+                    playbin.set_property("uri", uri);
+                }
+            }
+            None
+        });
+
         Self {
             playbin,
             state: None,
             bus,
             // uri: None,
+            next_uri,
         }
     }
 
@@ -164,6 +309,60 @@ impl Player {
         }
     }
 
+    /// Sets the file played right after the current one ends, without rebuilding the pipeline.
+    /// `None` lets the playback end with the current file
+    pub fn queue_next(&self, uri: Option<String>) {
+        *self.next_uri.lock().unwrap() = uri;
+    }
+
+    /// Returns the uri of the file being played
+    pub fn current_uri(&self) -> Option<String> {
+        self.playbin.property("current-uri")
+    }
+
+    /// Describes the audio stream playbin selected, its codec and container from the tags and the
+    /// format, sample rate and channels of the decoded audio, `None` if nothing is loaded
+    pub fn audio_info(&self) -> Option<String> {
+        let index: i32 = self.playbin.property("current-audio");
+        let mut parts = Vec::new();
+
+        if let Some(tags) =
+            self.playbin.emit_by_name::<Option<gst::TagList>>("get-audio-tags", &[&index])
+        {
+            if let Some(codec) = tags.get::<gst::tags::AudioCodec>() {
+                parts.push(codec.get().to_string());
+            }
+            if let Some(container) = tags.get::<gst::tags::ContainerFormat>() {
+                parts.push(format!("in {}", container.get()));
+            }
+            if let Some(bitrate) = tags.get::<gst::tags::Bitrate>() {
+                parts.push(format!("{} kbps", bitrate.get() / 1000));
+            }
+        }
+
+        let pad = self.playbin.emit_by_name::<Option<gst::Pad>>("get-audio-pad", &[&index]);
+        if let Some(caps) = pad.and_then(|it| it.current_caps()) {
+            if let Some(structure) = caps.structure(0) {
+                if let Ok(format) = structure.get::<String>("format") {
+                    parts.push(format);
+                }
+                if let Ok(rate) = structure.get::<i32>("rate") {
+                    parts.push(format!("{rate} Hz"));
+                }
+                if let Ok(channels) = structure.get::<i32>("channels") {
+                    parts.push(format!("{channels} channels"));
+                }
+            }
+        }
+
+        if parts.is_empty() { None } else { Some(parts.join(", ")) }
+    }
+
+    /// Returns the duration of the whole loaded file, `None` if it isn't known yet
+    pub fn get_duration_sec(&self) -> Option<u64> {
+        self.playbin.query_duration().map(gstreamer::ClockTime::seconds)
+    }
+
     pub fn get_total_duration(&self) -> gstreamer::ClockTime {
         self.playbin.query_duration().unwrap()
     }
@@ -174,7 +373,16 @@ impl Player {
         Ok(())
     }
 
-    pub fn load_chapter(&mut self, chapter: &Chapter, path: &Path, speed: f64, volume: f64) {
+    /// Loads the `chapter` and seeks to its last position. If the chapter wasn't played yet
+    /// `intro_skip` seconds are skipped from its start
+    pub fn load_chapter(
+        &mut self,
+        chapter: &Chapter,
+        path: &Path,
+        speed: f64,
+        volume: f64,
+        intro_skip: u64,
+    ) {
         let mut path = path.to_path_buf();
         path.push(&chapter.filename);
 
@@ -182,10 +390,13 @@ impl Player {
         self.playbin.set_property("volume", volume);
         self.pause();
 
-        let pos = if chapter.last_position != 0 {
+        let start_position = chapter.get_start_position();
+        let pos = if chapter.last_position != 0 && chapter.last_position != start_position {
             chapter.last_position
+        } else if intro_skip < chapter.length {
+            start_position + intro_skip
         } else {
-            chapter.get_start_position()
+            start_position
         };
         self.set_speed_and_position(speed, gstreamer::ClockTime::from_seconds(pos)).unwrap();
     }
@@ -248,6 +459,14 @@ impl Player {
         }
     }
 
+    /// Stores the current position as the `last_position` of the `chapter`, so less progress is
+    /// lost if the player exits unexpectedly. Does nothing if the position can't be queried
+    pub fn flush_position(&self, chapter: &mut Chapter) {
+        if let Some(pos) = self.get_position_sec() {
+            chapter.update_last_position(pos);
+        }
+    }
+
     /// Returns `true` if player is playing and pauses the playback, returns `false` otherwise
     pub fn is_playing_and_pause(&mut self) -> bool {
         if self.is_playing() {
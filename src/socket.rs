@@ -0,0 +1,190 @@
+//! Scripting interface over a Unix domain socket
+//!
+//! Every connected client receives events as JSON objects, one per line, e.g.
+//! `{"event":"chapter_change","index":3,"title":"Chapter 4"}`. Clients can send commands, one
+//! per line: `play`, `pause`, `next` and `seek N` where `N` is a position inside the current
+//! chapter in seconds.
+
+use std::io::{BufRead,
+              BufReader,
+              Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener,
+                         UnixStream};
+use std::path::{Path,
+                PathBuf};
+use std::sync::mpsc::{self,
+                      Receiver,
+                      Sender};
+use std::sync::{Arc,
+                Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// How long sending to a client can block before the client is dropped, so a client that doesn't
+/// read can't freeze the player
+const WRITE_TIMEOUT: Duration = Duration::from_millis(20);
+
+/// Command received from a client
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Play,
+    Pause,
+    Next,
+    Seek(u64),
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        let command = match words.next()? {
+            "play" => Command::Play,
+            "pause" => Command::Pause,
+            "next" => Command::Next,
+            "seek" => Command::Seek(words.next()?.parse().ok()?),
+            _ => return None,
+        };
+
+        match words.next() {
+            Some(_) => None,
+            None => Some(command),
+        }
+    }
+}
+
+/// Event sent to every client
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    Play,
+    Pause,
+    ChapterChange { index: usize, title: &'a str },
+    Position { position: u64, length: u64 },
+    EndOfBook,
+}
+
+pub struct Socket {
+    path: PathBuf,
+    clients: Arc<Mutex<Vec<UnixStream>>>,
+    commands: Receiver<Command>,
+    last_position: Option<u64>, // last position sent to the clients
+}
+
+impl Socket {
+    /// Binds a socket under the `path`, replacing a stale one, and starts accepting clients on a
+    /// background thread. Anything other than a socket under the `path` is left alone
+    pub fn bind(path: &Path) -> std::io::Result<Self> {
+        match std::fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+            Ok(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("{} already exists and isn't a socket", path.display()),
+                ));
+            }
+            Err(_) => (),
+        }
+
+        let listener = UnixListener::bind(path)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let (sender, commands) = mpsc::channel();
+
+        {
+            let clients = Arc::clone(&clients);
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+                        continue;
+                    }
+                    if let Ok(clone) = stream.try_clone() {
+                        clients.lock().unwrap().push(clone);
+                    }
+                    let sender = sender.clone();
+                    std::thread::spawn(move || read_commands(stream, sender));
+                }
+            });
+        }
+
+        Ok(Self { path: path.to_owned(), clients, commands, last_position: None })
+    }
+
+    /// Returns the next command sent by any of the clients
+    pub fn try_recv(&self) -> Option<Command> {
+        self.commands.try_recv().ok()
+    }
+
+    /// Sends the `event` to all the clients dropping the ones that disconnected or didn't read
+    /// what was sent before in time
+    pub fn emit(&self, event: &Event) {
+        let mut line = match serde_json::to_string(event) {
+            Ok(ok) => ok,
+            Err(_) => return,
+        };
+        line.push('\n');
+
+        self.clients.lock().unwrap().retain_mut(|it| it.write_all(line.as_bytes()).is_ok());
+    }
+
+    /// Sends the position only if it changed since it was last sent
+    pub fn emit_position(&mut self, position: u64, length: u64) {
+        if self.last_position != Some(position) {
+            self.last_position = Some(position);
+            self.emit(&Event::Position { position, length });
+        }
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn read_commands(stream: UnixStream, sender: Sender<Command>) {
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(ok) => ok,
+            Err(_) => return,
+        };
+
+        if let Some(command) = Command::parse(&line) {
+            if sender.send(command).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(Command::parse("play"), Some(Command::Play));
+        assert_eq!(Command::parse(" pause "), Some(Command::Pause));
+        assert_eq!(Command::parse("next"), Some(Command::Next));
+        assert_eq!(Command::parse("seek 90"), Some(Command::Seek(90)));
+    }
+
+    #[test]
+    fn parse_invalid_commands() {
+        assert_eq!(Command::parse(""), None);
+        assert_eq!(Command::parse("seek"), None);
+        assert_eq!(Command::parse("seek -5"), None);
+        assert_eq!(Command::parse("play now"), None);
+        assert_eq!(Command::parse("stop"), None);
+    }
+
+    #[test]
+    fn bind_leaves_other_files_alone() {
+        let path = std::env::temp_dir().join(format!("gadacz-socket-test-{}", std::process::id()));
+        std::fs::write(&path, "keep me").unwrap();
+
+        let err = Socket::bind(&path).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
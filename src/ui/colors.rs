@@ -0,0 +1,95 @@
+//! Colors of the playlist read from the config
+//!
+//! Colors are written as a name (`"green"`, `"light_green"`, `"dark_gray"`) or as a hex code
+//! (`"#a0c0ff"`).
+
+use tui::style::Color;
+
+use crate::data::config::ProgressColor;
+
+/// Parses the notation described in the module docs
+pub fn parse(input: &str) -> Option<Color> {
+    let color = match input.to_lowercase().as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::Gray,
+        "dark_gray" => Color::DarkGray,
+        "light_red" => Color::LightRed,
+        "light_green" => Color::LightGreen,
+        "light_yellow" => Color::LightYellow,
+        "light_blue" => Color::LightBlue,
+        "light_magenta" => Color::LightMagenta,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        other => {
+            let hex = other.strip_prefix('#')?;
+            if hex.len() != 6 || !hex.chars().all(|it| it.is_ascii_hexdigit()) {
+                return None;
+            }
+            let value = u32::from_str_radix(hex, 16).ok()?;
+            Color::Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8)
+        }
+    };
+
+    Some(color)
+}
+
+/// Turns the `progress_colors` of the config into thresholds sorted from the highest. Returns them
+/// and descriptions of the entries that were skipped
+pub fn progress_colors(config: &[ProgressColor]) -> (Vec<(u16, Color)>, Vec<String>) {
+    let mut errors = Vec::new();
+    let mut colors: Vec<_> = config
+        .iter()
+        .filter_map(|it| match parse(&it.color) {
+            Some(color) => Some((it.from, color)),
+            None => {
+                errors.push(format!("Skipped the unknown progress color {}", it.color));
+                None
+            }
+        })
+        .collect();
+    colors.sort_by_key(|it| std::cmp::Reverse(it.0));
+
+    (colors, errors)
+}
+
+/// Returns the color of the highest threshold the `percent` reaches, dark gray if it reaches none
+pub fn progress_color(colors: &[(u16, Color)], percent: u16) -> Color {
+    colors.iter().find(|it| percent >= it.0).map_or(Color::DarkGray, |it| it.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_names_and_hex_codes() {
+        assert_eq!(parse("light_green"), Some(Color::LightGreen));
+        assert_eq!(parse("Dark_Gray"), Some(Color::DarkGray));
+        assert_eq!(parse("#a0c0ff"), Some(Color::Rgb(0xa0, 0xc0, 0xff)));
+        assert_eq!(parse("#a0c0f"), None);
+        assert_eq!(parse("#+0c0ff"), None);
+        assert_eq!(parse("greenish"), None);
+    }
+
+    #[test]
+    fn highest_reached_threshold_wins() {
+        let config = [
+            ProgressColor { from: 50, color: "yellow".to_string() },
+            ProgressColor { from: 90, color: "green".to_string() },
+            ProgressColor { from: 10, color: "nope".to_string() },
+        ];
+        let (colors, errors) = progress_colors(&config);
+        assert_eq!(colors, vec![(90, Color::Green), (50, Color::Yellow)]);
+        assert_eq!(errors.len(), 1);
+
+        assert_eq!(progress_color(&colors, 100), Color::Green);
+        assert_eq!(progress_color(&colors, 50), Color::Yellow);
+        assert_eq!(progress_color(&colors, 49), Color::DarkGray);
+    }
+}
@@ -16,17 +16,26 @@ use tui::widgets::{Block,
                    Paragraph,
                    Wrap};
 
+use crate::data::chapter::formatted_time;
 use crate::data::mediainfo::MediaInfo;
+use crate::helpers::format_chapter_position;
 use crate::App;
 
+pub mod colors;
 pub mod popouts;
 
 pub struct Ui {
     pub chapter_bar: u16,
+    pub chapter_bar_area: Option<Rect>, // where the chapter progress bar was drawn, for the mouse
     pub volume_bar: u16,
 
     pub yn_prompt: &'static str, // text for yes/no prompt
 
+    pub show_filenames: bool, // if true the playlist shows filenames instead of titles
+    pub view_order: Option<Vec<usize>>, // display order of the playlist, if not natural
+    pub compact_info: bool,   // if true the info panel only shows the chapter
+    pub show_session: bool,   // if true the length of the listening session is shown
+
     // pub keybindings_list: Vec<ListItem<'static>>,
     pub keybindings_list: Vec<&'static str>,
 }
@@ -35,41 +44,103 @@ impl Ui {
     pub fn new() -> Self {
         Self {
             chapter_bar: 0,
+            chapter_bar_area: None,
             volume_bar: 50,
             yn_prompt: "NONE",
+            show_filenames: false,
+            view_order: None,
+            compact_info: false,
+            show_session: false,
             keybindings_list: vec![
                 "? : List all shortcuts",
-                "= : Increase volume by 5%",
-                "- : Decrease volume by 5%",
+                "= : Increase volume by volume_step from the config (5% by default)",
+                "- : Decrease volume by volume_step from the config (5% by default)",
                 "v : Set arbitrary volume",
                 "; : Jump to arbitrary position",
                 "a : Add new bookmark",
+                "A : Add new bookmark without asking for a name",
                 "b : Bookmark menu (only this chapter)",
                 "B : Bookmark menu (all chapters)",
-                "h : Move 5 seconds backwards",
+                "/ : Find a bookmark by name (all chapters)",
+                "g : Go to a chapter by its number",
+                "G : Go to a chapter by its track number (within the file for m4b)",
+                "f : Find a chapter by its title or description",
+                "E : Export descriptions and bookmarks to notes.md",
+                "' : Go to the bookmark selected last again",
+                "> : Go to the next bookmark (all chapters)",
+                "< : Go to the previous bookmark (all chapters)",
+                "h : Move backwards by the seek step (5 seconds by default)",
                 "j : Move 1 chapter forwards",
                 "k : Move 1 chapter backwards",
-                "l : Move 5 seconds forwards",
+                "C-j, End : Move to the last chapter",
+                "C-k, Home : Move to the first chapter",
+                "c : Recenter the playlist on the current chapter",
+                "J : Move the current chapter down the playlist",
+                "K : Move the current chapter up the playlist",
+                "X : Remove the current chapter from the playlist",
+                "l : Move forwards by the seek step (5 seconds by default)",
+                "C-l : Set the seek step",
+                "H : Move 5 minutes (big_jump_secs in the config) backwards",
+                "L : Move 5 minutes (big_jump_secs in the config) forwards",
                 "p : Toggle pause and play",
-                "q : Quit",
+                "q : Quit (also C-c)",
                 "r : Reset progress of the chapter",
+                "U : Reset progress of the whole book and start from the first chapter",
+                "0 : Go to the start of the chapter",
+                "P : Show the position in the book (in the file with relative_positions)",
                 "s : Increase speed by 0.25",
                 "S : Decrease speed by 0.25",
+                "] : Increase speed by 0.05",
+                "[ : Decrease speed by 0.05",
                 "C-s : Set arbitrary speed",
+                "x : Cycle the speed through the presets (speed_presets in the config)",
+                "R : Repeat the chapter a number of times before going on",
+                "u : Set the A and then the B point of a repeated loop, pressing again clears it",
+                "w : Skim, the speed goes up while w is held and back down after",
+                "C-p : Save speed and volume as the defaults for new books",
                 "m : Mark position for a bookmark",
                 "M : Create bookmark at the marked position",
                 "d : Set description for the current chapter",
+                "n : Show the note for the whole book",
+                "C-n : Edit the note for the whole book",
                 "D : Delete description for the current chapter",
                 "z : Save position",
                 "Z : Restore saved position",
                 "F : Set 100% completion and move to next chapter",
+                "N : Toggle starting the next chapter when the current one ends",
+                "i : Skip up to the current position at the start of new chapters",
                 ": : Go to the position before the jump or bookmark(for current chapter) change",
                 ", : Go to position and chapter before the bookmark(for all chapters) change",
                 "C-a : Toggle antispoiler mode",
+                "C-b : Toggle marking the book as finished",
+                "C-f : Toggle fading in and out on play and pause",
+                "C-r : Toggle moving backwards past the start to the previous chapter",
+                "C-o : Toggle keeping the current speed and volume for this chapter only",
+                "C-e : Set arbitrary speed for this chapter only",
+                "C-d : Show the current state (for bug reports)",
+                "C-g : Show the codec, sample rate and channels of the playing file",
+                "t : Toggle showing filenames instead of titles in the playlist",
+                "O : Choose the audio output device",
+                "V : Open the current file with xdg-open ($GADACZ_OPEN if set)",
+                "I : Toggle between the compact and detailed info panel",
+                "C-t : Set the sleep timer, pressing again adds more minutes",
+                "C-x : Cancel the sleep timer",
+                "e : Toggle showing the time elapsed in this listening session",
+                "T : Trim whitespace from titles and descriptions of all chapters",
+                "o : Toggle showing the playlist sorted by completion",
             ],
         }
     }
 
+    /// Returns how far into the chapter progress bar the cell at `column` and `row` lies, from 0.0
+    /// to 1.0, `None` if it's outside of the bar
+    pub fn chapter_bar_fraction(&self, column: u16, row: u16) -> Option<f64> {
+        let area = self.chapter_bar_area?;
+        let inside = (area.left()..area.right()).contains(&column)
+            && (area.top()..area.bottom()).contains(&row);
+        if inside { Some((column - area.x) as f64 / area.width as f64) } else { None }
+    }
+
     pub fn on_tick(&mut self, volume: f64, position: u64, length: u64) {
         self.volume_bar = (volume * 100.0) as u16;
         self.chapter_bar = ((position as f64 / length as f64) * 100.0) as u16;
@@ -111,9 +182,10 @@ pub fn render<B: Backend>(f: &mut tui::Frame<B>, app: &mut App, mediainfo: &Medi
         .split(main_chunk[0]);
 
     // Info Block
+    let title = if mediainfo.is_finished { "Info ✓" } else { "Info" };
     let block = Block::default()
         .title_alignment(Alignment::Center)
-        .title(tui::text::Span::styled("Info", Style::default().fg(Color::White)));
+        .title(tui::text::Span::styled(title, Style::default().fg(Color::White)));
     f.render_widget(block, top_chunks[0]);
 
     let info_split = Layout::default()
@@ -136,26 +208,68 @@ pub fn render<B: Backend>(f: &mut tui::Frame<B>, app: &mut App, mediainfo: &Medi
         .constraints([Constraint::Max(12), Constraint::Percentage(40)])
         .split(info_split[0]);
 
-    let info_info = List::new(&*app.cache.info_info).style(Style::default().fg(Color::White));
+    // in the compact mode only the chapter is shown
+    let info_rows = match (app.ui.compact_info, app.relative_positions) {
+        (true, _) => 1,
+        // the last rows show the position in the file
+        (false, true) => app.cache.info_info.len() - 3,
+        (false, false) => app.cache.info_info.len(),
+    };
+
+    let info_info =
+        List::new(&app.cache.info_info[..info_rows]).style(Style::default().fg(Color::White));
     f.render_widget(info_info, info_chunks[0]);
 
-    let items = vec![
+    if app.cache.bks_total.is_none() {
+        let with_bookmarks = mediainfo.chapters.iter().filter(|it| !it.bookmarks.is_empty());
+        let (total, chapters) = with_bookmarks
+            .fold((0, 0), |(total, chapters), it| (total + it.bookmarks.len(), chapters + 1));
+        app.cache.bks_total = Some(format!("{total} total across {chapters} chapters"));
+    }
+
+    // information from the tags takes precedence over the sidecar files
+    let book_info = mediainfo.book_info.as_ref();
+    let mut items = vec![
         ListItem::new(current_chapter.get_name()),
-        ListItem::new(current_chapter.album.as_deref().unwrap_or("None")),
-        ListItem::new(current_chapter.artist.as_deref().unwrap_or("None")),
+        ListItem::new(
+            current_chapter
+                .album
+                .as_deref()
+                .or_else(|| book_info?.title.as_deref())
+                .unwrap_or("None"),
+        ),
+        ListItem::new(
+            current_chapter
+                .artist
+                .as_deref()
+                .or_else(|| book_info?.author.as_deref())
+                .unwrap_or("None"),
+        ),
+        ListItem::new(book_info.and_then(|it| it.narrator.as_deref()).unwrap_or("None")),
+        ListItem::new(book_info.and_then(|it| it.series.as_deref()).unwrap_or("None")),
+        ListItem::new(app.cache.bks_total.as_deref().unwrap_or("None")),
         ListItem::new(&*current_chapter.filename),
         ListItem::new(mediainfo.path.display().to_string()),
-        ListItem::new(mediainfo.speed.to_string()),
+        ListItem::new(match (current_chapter.speed_override, &app.skim) {
+            (_, Some(_)) => {
+                format!("{} (skimming)", mediainfo.speed_at(app.current_chapter_index))
+            }
+            (Some(speed), None) => format!("{speed} (chapter override)"),
+            (None, None) => mediainfo.speed.to_string(),
+        }),
         ListItem::new(current_chapter.start_position.unwrap_or(0).to_string()),
         ListItem::new(app.cache.formatted_abs_now.as_deref().unwrap_or("None")),
         ListItem::new(app.cache.abs_now.as_deref().unwrap_or("None")),
     ];
+    items.truncate(info_rows);
     let list = List::new(items).style(Style::default().fg(Color::White));
     f.render_widget(list, info_chunks[1]);
 
     // progress bar
+    let chapter_bar_block = Block::default().borders(Borders::NONE).title("Chapter Progress");
+    app.ui.chapter_bar_area = Some(chapter_bar_block.inner(info_split[2]));
     let chapter_bar = Gauge::default()
-        .block(Block::default().borders(Borders::NONE).title("Chapter Progress"))
+        .block(chapter_bar_block)
         .gauge_style(
             Style::default().fg(Color::White).bg(Color::Black).add_modifier(Modifier::ITALIC),
         )
@@ -175,24 +289,70 @@ pub fn render<B: Backend>(f: &mut tui::Frame<B>, app: &mut App, mediainfo: &Medi
         .percent(app.ui.volume_bar);
     f.render_widget(volume_bar, info_split[4]);
 
+    if app.cache.book_progress.is_none() {
+        let (listened, total) = mediainfo.progress();
+        let percent = (listened * 100).checked_div(total).unwrap_or(0);
+        app.cache.book_progress =
+            Some(format!("{percent}% — {} left", formatted_time(total - listened)));
+    }
+
     // extra information
     {
-        if let Some(pos) = app.marked_position {
-            let info = Layout::default()
-                .direction(Direction::Horizontal)
-                .margin(1)
-                .constraints([Constraint::Percentage(13), Constraint::Percentage(50)])
-                .split(info_split[6]);
+        let mut left_items = vec![ListItem::new("Book: ")];
+        let mut right_items =
+            vec![ListItem::new(app.cache.book_progress.as_deref().unwrap_or("None"))];
+
+        if let Some(pos) = mediainfo.marked_position {
+            left_items.push(ListItem::new("Marked Position: "));
+            right_items.push(ListItem::new(format_chapter_position(
+                pos,
+                current_chapter.start_position,
+                app.relative_positions,
+            )));
+        }
 
-            let left_items = vec![ListItem::new("Marked Position: ")];
-            let left_list = List::new(left_items).style(Style::default().fg(Color::White));
+        if let Some(cover) = mediainfo.cover.as_ref().and_then(|it| it.file_name()) {
+            left_items.push(ListItem::new("Cover: "));
+            right_items.push(ListItem::new(cover.to_string_lossy()));
+        }
 
-            let right_item = vec![ListItem::new(pos.to_string())];
-            let right_list = List::new(right_item).style(Style::default().fg(Color::White));
+        if app.ui.show_session {
+            left_items.push(ListItem::new("Session: "));
+            right_items.push(ListItem::new(formatted_time(app.session_start.elapsed().as_secs())));
+        }
+
+        if let Some((_, a, b)) = app.ab_loop {
+            let start_position = current_chapter.start_position;
+            left_items.push(ListItem::new("A-B loop: "));
+            right_items.push(ListItem::new(format!(
+                "{}–{}",
+                format_chapter_position(a, start_position, app.relative_positions),
+                b.map(|b| format_chapter_position(b, start_position, app.relative_positions))
+                    .unwrap_or_default()
+            )));
+        }
 
-            f.render_widget(left_list, info[0]);
-            f.render_widget(right_list, info[1]);
+        if app.repeat_remaining > 0 {
+            left_items.push(ListItem::new("Repeats: "));
+            right_items.push(ListItem::new(format!("{} left", app.repeat_remaining)));
         }
+
+        if let Some((_, countdown)) = &app.cache.sleep_countdown {
+            left_items.push(ListItem::new("Sleep in: "));
+            right_items.push(ListItem::new(countdown.as_str()));
+        }
+
+        let info = Layout::default()
+            .direction(Direction::Horizontal)
+            .margin(1)
+            .constraints([Constraint::Percentage(13), Constraint::Percentage(50)])
+            .split(info_split[6]);
+
+        let left_list = List::new(left_items).style(Style::default().fg(Color::White));
+        let right_list = List::new(right_items).style(Style::default().fg(Color::White));
+
+        f.render_widget(left_list, info[0]);
+        f.render_widget(right_list, info[1]);
     }
 
     // Playlist space
@@ -216,32 +376,23 @@ pub fn render<B: Backend>(f: &mut tui::Frame<B>, app: &mut App, mediainfo: &Medi
         .split(top_chunks[1]);
 
     let playlist_height: usize = playlist_chunk[0].height.into();
-    let number_of_rest_tracks = mediainfo.chaptercount - (app.current_chapter_index + 1);
-
-    // calculate how many chapters to skip for rendering inside the playlist chunk
-    let skip = if (app.current_chapter_index + 1) >= playlist_height {
-        let s = (app.current_chapter_index + 1) - playlist_height;
-
-        // add different ammount of padding (so it always shows two tracks at the bottom and
-        // fills ups the playlist chunk) according to how many tracks are there left
-        if number_of_rest_tracks == 0 {
-            s
-        } else if number_of_rest_tracks == 1 {
-            1 + s
-        } else {
-            2 + s
-        }
-    } else {
-        usize::from(playlist_height - (app.current_chapter_index + 1) == 1)
-    };
 
-    // NumberType::from(playlist_height - (app.current_chapter_index + 1) == 1)
+    // indices of chapters in the order they are shown in the playlist
+    let order: Vec<usize> = match app.ui.view_order.as_ref() {
+        Some(order) => order.clone(),
+        None => (0..mediainfo.chaptercount()).collect(),
+    };
+    let current_view_index = order
+        .iter()
+        .position(|&i| i == app.current_chapter_index)
+        .unwrap_or(app.current_chapter_index);
+    let skip = playlist_skip(current_view_index, mediainfo.chaptercount(), playlist_height);
 
     if let Some(pl_percentages) = app.cache.pl_percentages.as_ref() {
         let list = List::new(&**pl_percentages);
         f.render_widget(list, playlist_chunk[0]);
     } else {
-        new_percentages(mediainfo, skip, playlist_height, app);
+        new_percentages(mediainfo, &order, skip, playlist_height, app);
         let list = List::new(&**app.cache.pl_percentages.as_ref().unwrap());
         f.render_widget(list, playlist_chunk[0]);
     }
@@ -250,17 +401,23 @@ pub fn render<B: Backend>(f: &mut tui::Frame<B>, app: &mut App, mediainfo: &Medi
         let choses_list = List::new(&**chooses);
         f.render_widget(choses_list, playlist_chunk[1]);
     } else {
-        let chooses: Vec<_> = mediainfo
-            .chapters
+        let chooses: Vec<_> = order
             .iter()
             .skip(skip)
             .take(playlist_height)
-            .enumerate()
-            .map(|(i, _)| {
-                if skip + i == app.current_chapter_index {
-                    ListItem::new(">>> ").style(Style::default().fg(Color::Red))
+            .map(|&i| {
+                let chapter = &mediainfo.chapters[i];
+                // marks chapters with their own speed or volume
+                let mark = if chapter.speed_override.is_some() || chapter.volume_override.is_some()
+                {
+                    "~"
+                } else {
+                    " "
+                };
+                if i == app.current_chapter_index {
+                    ListItem::new(format!(">>>{mark}")).style(Style::default().fg(Color::Red))
                 } else {
-                    ListItem::new("    ")
+                    ListItem::new(format!("   {mark}"))
                 }
             })
             .collect();
@@ -273,7 +430,7 @@ pub fn render<B: Backend>(f: &mut tui::Frame<B>, app: &mut App, mediainfo: &Medi
         let list = List::new(&**titles).style(Style::default().fg(Color::White));
         f.render_widget(list, playlist_chunk[2]);
     } else {
-        new_titles(mediainfo, skip, playlist_height, app);
+        new_titles(mediainfo, &order, skip, playlist_height, app);
         let list = List::new(&**app.cache.pl_titles.as_ref().unwrap())
             .style(Style::default().fg(Color::White));
         f.render_widget(list, playlist_chunk[2]);
@@ -283,7 +440,7 @@ pub fn render<B: Backend>(f: &mut tui::Frame<B>, app: &mut App, mediainfo: &Medi
         let list = List::new(&**lengths).style(Style::default().fg(Color::White));
         f.render_widget(list, playlist_chunk[4]);
     } else {
-        new_lengths(mediainfo, skip, playlist_height, app);
+        new_lengths(mediainfo, &order, skip, playlist_height, app);
         let list = List::new(&**app.cache.pl_lengths.as_ref().unwrap())
             .style(Style::default().fg(Color::White));
         f.render_widget(list, playlist_chunk[4]);
@@ -293,7 +450,7 @@ pub fn render<B: Backend>(f: &mut tui::Frame<B>, app: &mut App, mediainfo: &Medi
         let list = List::new(&**pl_bks_count).style(Style::default().fg(Color::White));
         f.render_widget(list, playlist_chunk[6]);
     } else {
-        new_bks_counts(mediainfo, skip, playlist_height, app);
+        new_bks_counts(mediainfo, &order, skip, playlist_height, app);
         let list = List::new(&**app.cache.pl_bks_count.as_ref().unwrap())
             .style(Style::default().fg(Color::White));
         f.render_widget(list, playlist_chunk[6]);
@@ -334,7 +491,12 @@ pub fn render<B: Backend>(f: &mut tui::Frame<B>, app: &mut App, mediainfo: &Medi
                 .bookmarks
                 .iter()
                 .take(bk_height)
-                .map(|it| ListItem::new(it.formatted_position.clone()))
+                .map(|it| {
+                    ListItem::new(
+                        it.display(current_chapter.start_position, app.relative_positions)
+                            .into_owned(),
+                    )
+                })
                 .collect();
             app.cache.bk_list0 = Some(bk0);
         }
@@ -345,7 +507,12 @@ pub fn render<B: Backend>(f: &mut tui::Frame<B>, app: &mut App, mediainfo: &Medi
                 .iter()
                 .skip(bk_height)
                 .take(bk_height)
-                .map(|it| ListItem::new(it.formatted_position.clone()))
+                .map(|it| {
+                    ListItem::new(
+                        it.display(current_chapter.start_position, app.relative_positions)
+                            .into_owned(),
+                    )
+                })
                 .collect();
             app.cache.bk_list1 = Some(bk1);
         }
@@ -481,14 +648,21 @@ pub fn centered_rect_flat(flat_x: u16, flat_y: u16, r: Rect) -> Option<Rect> {
 }
 
 // Computes new lengths to be shown in the playlist chunk and assigns them to cache
-fn new_lengths(mediainfo: &MediaInfo, skip: usize, playlist_height: usize, app: &mut App) {
+fn new_lengths(
+    mediainfo: &MediaInfo,
+    order: &[usize],
+    skip: usize,
+    playlist_height: usize,
+    app: &mut App,
+) {
     app.cache.pl_lengths = Some(
         // if mediainfo.is_antispoiler {
         if mediainfo.is_antispoiler {
-            (0..playlist_height)
-                .into_iter()
+            order
+                .iter()
                 .skip(skip)
-                .map(|i| {
+                .take(playlist_height)
+                .map(|&i| {
                     if mediainfo.is_antispoiler && i > app.current_chapter_index {
                         ListItem::new("######").style(Style::default().fg(Color::White))
                     } else {
@@ -497,20 +671,36 @@ fn new_lengths(mediainfo: &MediaInfo, skip: usize, playlist_height: usize, app:
                 })
                 .collect()
         } else {
-            mediainfo
-                .chapters
+            order
                 .iter()
                 .skip(skip)
                 .take(playlist_height)
-                .map(|x| ListItem::new(x.length_display.clone()))
+                .map(|&i| ListItem::new(mediainfo.chapters[i].length_display.clone()))
                 .collect()
         },
     );
 }
 
+/// Calculates how many chapters to skip for rendering inside the playlist chunk, so the `current`
+/// one is always shown with up to two of the next ones below it, as many as fit in the `height`
+fn playlist_skip(current: usize, count: usize, height: usize) -> usize {
+    if height == 0 {
+        return 0;
+    }
+    let below = count.saturating_sub(current + 1).min(2).min(height - 1);
+    (current + below + 1).saturating_sub(height)
+}
+
 // Computes new percentages to be shown in the playlist chunk and assigns them to cache
-fn new_percentages(mediainfo: &MediaInfo, skip: usize, playlist_height: usize, app: &mut App) {
+fn new_percentages(
+    mediainfo: &MediaInfo,
+    order: &[usize],
+    skip: usize,
+    playlist_height: usize,
+    app: &mut App,
+) {
     fn format_perc(
+        colors: &[(u16, Color)],
         start_position: Option<u64>,
         last_position: u64,
         length: u64,
@@ -522,86 +712,113 @@ fn new_percentages(mediainfo: &MediaInfo, skip: usize, playlist_height: usize, a
 
             if p >= 100 { 100 } else { p }
         };
-        let string = format!("{perc}%");
-        if perc >= 75 {
-            ListItem::new(string).style(Style::default().fg(Color::Green))
-        } else if perc >= 50 {
-            ListItem::new(string).style(Style::default().fg(Color::LightGreen))
-        } else if perc >= 25 {
-            ListItem::new(string).style(Style::default().fg(Color::Gray))
-        } else {
-            ListItem::new(string).style(Style::default().fg(Color::DarkGray))
-        }
+        ListItem::new(format!("{perc}%"))
+            .style(Style::default().fg(colors::progress_color(colors, perc)))
     }
 
+    let colors = &app.progress_colors;
     app.cache.pl_percentages = Some(if mediainfo.is_antispoiler {
-        (0..playlist_height)
-            .into_iter()
+        order
+            .iter()
             .skip(skip)
-            .map(|i| {
+            .take(playlist_height)
+            .map(|&i| {
                 if mediainfo.is_antispoiler && i > app.current_chapter_index {
                     ListItem::new("###").style(Style::default().fg(Color::White))
                 } else {
                     let x = &mediainfo.chapters[i];
-                    format_perc(x.start_position, x.last_position, x.length)
+                    format_perc(colors, x.start_position, x.last_position, x.length)
                 }
             })
             .collect()
     } else {
-        mediainfo
-            .chapters
+        order
             .iter()
             .skip(skip)
             .take(playlist_height)
-            .map(|x| format_perc(x.start_position, x.last_position, x.length))
+            .map(|&i| &mediainfo.chapters[i])
+            .map(|x| format_perc(colors, x.start_position, x.last_position, x.length))
             .collect()
     });
 }
 
 // Computes new titles to be shown in the playlist chunk and assigns them to cache
-fn new_titles(mediainfo: &MediaInfo, skip: usize, playlist_height: usize, app: &mut App) {
+fn new_titles(
+    mediainfo: &MediaInfo,
+    order: &[usize],
+    skip: usize,
+    playlist_height: usize,
+    app: &mut App,
+) {
+    let show_filenames = app.ui.show_filenames;
+    // chapters of a single file are tied together so they can be told apart from separate files,
+    // books where every file is one chapter are shown without the connectors
+    let has_shared_files = mediainfo.chapters.windows(2).any(|it| it[0].filename == it[1].filename);
+    let title = |i: usize| -> String {
+        let x = &mediainfo.chapters[i];
+        let title = if show_filenames { &x.filename } else { x.get_title_or_filename() };
+        let connector = if has_shared_files { file_connector(mediainfo, i) } else { "" };
+        if let Some(desc) = &x.description {
+            format!("{}{} [{}]", connector, title, desc)
+        } else {
+            format!("{}{}", connector, title)
+        }
+    };
+
+    let frontier = *app.cache.pl_frontier.get_or_insert_with(|| mediainfo.furthest_chapter());
+    // marks the furthest played chapter
+    let item = |i: usize, text: String| {
+        if Some(i) == frontier {
+            ListItem::new(text).style(Style::default().add_modifier(Modifier::UNDERLINED))
+        } else {
+            ListItem::new(text)
+        }
+    };
+
     app.cache.pl_titles = Some(if mediainfo.is_antispoiler {
-        (0..playlist_height)
-            .into_iter()
+        order
+            .iter()
             .skip(skip)
-            .map(|i| {
+            .take(playlist_height)
+            .map(|&i| {
                 if i > app.current_chapter_index {
                     ListItem::new("##########").style(Style::default().fg(Color::White))
                 } else {
-                    let x = &mediainfo.chapters[i];
-                    if let Some(desc) = &x.description {
-                        ListItem::new(format!("{} [{}]", x.get_title_or_filename(), desc))
-                    } else {
-                        ListItem::new(x.get_title_or_filename().clone())
-                    }
+                    item(i, title(i))
                 }
             })
             .collect()
     } else {
-        mediainfo
-            .chapters
-            .iter()
-            .skip(skip)
-            .take(playlist_height)
-            .map(|x| {
-                ListItem::new({
-                    if let Some(desc) = &x.description {
-                        format!("{} [{}]", x.get_title_or_filename(), desc)
-                    } else {
-                        x.get_title_or_filename().clone()
-                    }
-                })
-            })
-            .collect()
+        order.iter().skip(skip).take(playlist_height).map(|&i| item(i, title(i))).collect()
     })
 }
 
-fn new_bks_counts(mediainfo: &MediaInfo, skip: usize, playlist_height: usize, app: &mut App) {
+/// Returns a glyph connecting the chapter under `index` to the neighbouring chapters of the same
+/// file
+fn file_connector(mediainfo: &MediaInfo, index: usize) -> &'static str {
+    let filename = &mediainfo.chapters[index].filename;
+    let same_file = |i: Option<usize>| matches!(i.and_then(|i| mediainfo.chapters.get(i)), Some(it) if it.filename == *filename);
+    match (same_file(index.checked_sub(1)), same_file(Some(index + 1))) {
+        (false, true) => "┌ ",
+        (true, true) => "├ ",
+        (true, false) => "└ ",
+        (false, false) => "  ",
+    }
+}
+
+fn new_bks_counts(
+    mediainfo: &MediaInfo,
+    order: &[usize],
+    skip: usize,
+    playlist_height: usize,
+    app: &mut App,
+) {
     app.cache.pl_bks_count = Some(if mediainfo.is_antispoiler {
-        (0..playlist_height)
-            .into_iter()
+        order
+            .iter()
             .skip(skip)
-            .map(|i| {
+            .take(playlist_height)
+            .map(|&i| {
                 if i > app.current_chapter_index {
                     ListItem::new("#").style(Style::default().fg(Color::White))
                 } else {
@@ -610,12 +827,44 @@ fn new_bks_counts(mediainfo: &MediaInfo, skip: usize, playlist_height: usize, ap
             })
             .collect()
     } else {
-        mediainfo
-            .chapters
+        order
             .iter()
             .skip(skip)
             .take(playlist_height)
-            .map(|x| ListItem::new(x.bookmarks.len().to_string()))
+            .map(|&i| ListItem::new(mediainfo.chapters[i].bookmarks.len().to_string()))
             .collect()
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn playlist_skip_keeps_the_current_chapter_visible() {
+        // (height, current, expected skip) for a playlist of 10 chapters
+        let cases = [
+            (1, 0, 0),
+            (1, 5, 5),
+            (1, 9, 9),
+            (2, 0, 0),
+            (2, 5, 5),
+            (2, 9, 8),
+            (3, 0, 0),
+            (3, 5, 5),
+            (3, 9, 7),
+        ];
+        for (height, current, expected) in cases {
+            let skip = playlist_skip(current, 10, height);
+            assert_eq!(skip, expected, "height {height}, current {current}");
+            assert!((skip..skip + height).contains(&current));
+        }
+    }
+
+    #[test]
+    fn playlist_skip_shows_two_next_chapters() {
+        assert_eq!(playlist_skip(8, 20, 10), 1);
+        assert_eq!(playlist_skip(1, 2, 3), 0);
+        assert_eq!(playlist_skip(0, 0, 0), 0);
+    }
+}
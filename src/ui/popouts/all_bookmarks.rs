@@ -19,7 +19,8 @@ use tui::Terminal;
 
 use super::super::centered_rec_perc;
 use crate::data::mediainfo::MediaInfo;
-use crate::App;
+use crate::{ui,
+            App};
 
 fn render<B: Backend>(
     f: &mut tui::Frame<B>,
@@ -64,6 +65,7 @@ pub fn run<B: Backend>(
     }
 
     let was_playing = app.player.is_playing_and_pause();
+    app.player.flush_position(app.get_mut_current_chapter(mediainfo));
 
     let mut i = if let Some(index) = app.index_all_bookmark {
         if index >= len { len.saturating_sub(1) } else { index }
@@ -74,7 +76,9 @@ pub fn run<B: Backend>(
     let mut indexes = Vec::new();
 
     app.msgs.push(
-        "Press Enter to chose a bookmark. Press jk to move up and down. Press Escape to cancel."
+        "Press Enter to chose a bookmark. Press jk to move up and down. Press d to delete a \
+         bookmark and u to restore it. Press e to export them to bookmarks.txt. Press Escape to \
+         cancel."
             .into(),
     ); // this message will not disappear
     app.msgs.on_tick();
@@ -96,7 +100,7 @@ pub fn run<B: Backend>(
                             indexes.push((chapter_index, bk_index));
                             ListItem::new(format!(
                                 "{} | chapter name: {} | chapter number: {}",
-                                &*bk.formatted_position,
+                                bk.display(chapter.start_position, app.relative_positions),
                                 chapter.get_title_or_filename(),
                                 chapter.get_track_number()
                             ))
@@ -131,6 +135,45 @@ pub fn run<B: Backend>(
                         i = std::cmp::min(i.saturating_add(1), len - 1);
                     }
 
+                    KeyCode::Char('d' | 'u' | 'e') if mediainfo.is_readonly => {
+                        app.msgs.push("Read-only mode.".into());
+                    }
+
+                    KeyCode::Char('d') => {
+                        let res = !app.confirm.get("delete_bookmark", true)
+                            || ui::popouts::yn::run(
+                                terminal,
+                                app,
+                                mediainfo,
+                                last_tick,
+                                tick_rate,
+                                "Are you sure you want to delete the bookmark? y/n",
+                            )?;
+                        if res {
+                            let (chapter_index, bk_index) = indexes[i];
+                            app.delete_bookmark(mediainfo, chapter_index, bk_index);
+                            break None;
+                        }
+                    }
+
+                    KeyCode::Char('u') => {
+                        app.restore_deleted_bookmark(mediainfo);
+                        break None;
+                    }
+
+                    KeyCode::Char('e') => match mediainfo.export_bookmarks() {
+                        Ok(file) => {
+                            app.msgs.push(
+                                format!("Exported the bookmarks to {}", file.display()).into(),
+                            );
+                            app.msgs.on_tick();
+                        }
+                        Err(err) => {
+                            app.msgs.push(format!("Couldn't export the bookmarks: {err}").into());
+                            app.msgs.on_tick();
+                        }
+                    },
+
                     KeyCode::Enter => {
                         let (chapter_index, bk_index) = indexes[i];
                         break Some((Some(chapter_index), bk_index));
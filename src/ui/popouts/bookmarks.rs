@@ -19,6 +19,9 @@ use tui::Terminal;
 
 use super::super::centered_rec_perc;
 use crate::data::mediainfo::MediaInfo;
+use crate::helpers::{format_position,
+                     format_relative_time,
+                     unix_now};
 use crate::{ui,
             App};
 
@@ -62,6 +65,7 @@ pub fn run<B: Backend>(
     }
 
     let was_playing = app.player.is_playing_and_pause();
+    app.player.flush_position(app.get_mut_current_chapter(mediainfo));
 
     if let Some(pos) = app.player.get_position_sec() {
         app.get_mut_current_chapter(mediainfo).before_jump_position = Some(pos);
@@ -69,8 +73,15 @@ pub fn run<B: Backend>(
 
     let current_chapter = app.get_current_chapter(mediainfo);
 
-    let items: Vec<_> =
-        current_chapter.bookmarks.iter().map(|x| ListItem::new(&*x.formatted_position)).collect();
+    let items: Vec<_> = current_chapter
+        .bookmarks
+        .iter()
+        .map(|x| {
+            ListItem::new(
+                x.display(current_chapter.start_position, app.relative_positions).into_owned(),
+            )
+        })
+        .collect();
 
     let len = current_chapter.bookmarks.len();
 
@@ -82,7 +93,8 @@ pub fn run<B: Backend>(
 
     app.msgs.push(
         "Press Enter to chose a bookmark. Press j and k to move up and down. Press d to delete
-a bookmark. Press e to change a name of a bookmark. Press Escape to cancel."
+a bookmark or C to delete all bookmarks of the chapter, and u to restore them. Press e to
+change a name of a bookmark. Press i to show details of a bookmark. Press Escape to cancel."
             .into(),
     ); // this message will not disappear
     app.msgs.on_tick();
@@ -114,6 +126,10 @@ a bookmark. Press e to change a name of a bookmark. Press Escape to cancel."
                         break;
                     }
 
+                    KeyCode::Char('e' | 'd' | 'u' | 'C') if mediainfo.is_readonly => {
+                        app.msgs.push("Read-only mode.".into());
+                    }
+
                     KeyCode::Char('e') => {
                         let res = ui::popouts::input::run(
                             terminal,
@@ -123,7 +139,6 @@ a bookmark. Press e to change a name of a bookmark. Press Escape to cancel."
                             tick_rate,
                             "Change the name of the bookmark",
                             Some(&current_chapter.bookmarks[i].name),
-                            100,
                         )?;
 
                         if let Some(new_name) = res {
@@ -144,23 +159,69 @@ a bookmark. Press e to change a name of a bookmark. Press Escape to cancel."
                         break;
                     }
 
+                    KeyCode::Char('i') => {
+                        let bookmark = &current_chapter.bookmarks[i];
+                        let details = format!(
+                            "Name: {}\nPosition: {}s\nFormatted: {}\nChapter: {}\nNumber: \
+                             {}\nLast played: {}",
+                            bookmark.name,
+                            bookmark.position,
+                            format_position(bookmark.position, current_chapter.start_position),
+                            current_chapter.get_title_or_filename(),
+                            current_chapter
+                                .m4_tracknumber
+                                .or(current_chapter.tracknumber)
+                                .map_or_else(|| "-".to_string(), |it| it.to_string()),
+                            current_chapter
+                                .last_played_at
+                                .map(|it| format_relative_time(it, unix_now()))
+                                .unwrap_or_else(|| "never".to_string()),
+                        );
+
+                        ui::popouts::info::run(
+                            terminal,
+                            app,
+                            mediainfo,
+                            last_tick,
+                            tick_rate,
+                            "Bookmark details",
+                            &details,
+                        )?;
+                    }
+
                     KeyCode::Char('d') => {
+                        let res = !app.confirm.get("delete_bookmark", true)
+                            || ui::popouts::yn::run(
+                                terminal,
+                                app,
+                                mediainfo,
+                                last_tick,
+                                tick_rate,
+                                "Are you sure you want to delete the bookmark? y/n",
+                            )?;
+                        if res {
+                            app.delete_bookmark(mediainfo, app.current_chapter_index, i);
+                            break;
+                        }
+                    }
+
+                    KeyCode::Char('u') => {
+                        app.restore_deleted_bookmark(mediainfo);
+                        break;
+                    }
+
+                    KeyCode::Char('C') => {
                         let res = ui::popouts::yn::run(
                             terminal,
                             app,
                             mediainfo,
                             last_tick,
                             tick_rate,
-                            "Are you sure you want to delete the bookmark? y/n",
+                            "Are you sure you want to delete all bookmarks of this chapter? y/n",
                         )?;
                         if res {
-                            let delete = app.get_mut_current_chapter(mediainfo).delete_bookmark(i);
-                            app.msgs.push(
-                                format!("Deleted bookmark: {}", delete.formatted_position).into(),
-                            );
-
-                            app.cache.invalidate_bks();
-                            app.cache.pl_bks_count = None;
+                            app.delete_all_bookmarks(mediainfo, app.current_chapter_index);
+                            i = 0;
                             break;
                         }
                     }
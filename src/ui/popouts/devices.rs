@@ -0,0 +1,118 @@
+use std::time::{Duration,
+                Instant};
+
+use crossterm::event::{self,
+                       Event,
+                       KeyCode};
+use gst::prelude::*;
+use gstreamer as gst;
+use tui::backend::Backend;
+use tui::layout::Alignment;
+use tui::style::{Color,
+                 Modifier,
+                 Style};
+use tui::widgets::{Block,
+                   Borders,
+                   Clear,
+                   List,
+                   ListItem,
+                   ListState};
+use tui::Terminal;
+
+use super::super::centered_rec_perc;
+use crate::data::mediainfo::MediaInfo;
+use crate::player::{audio_devices,
+                    AudioSink};
+use crate::{actions,
+            App};
+
+fn render<B: Backend>(
+    f: &mut tui::Frame<B>,
+    app: &mut App,
+    mediainfo: &MediaInfo,
+    items: &[ListItem],
+    index: usize,
+) {
+    super::super::render(f, app, mediainfo);
+
+    let popout = centered_rec_perc(50, 50, f.size());
+    let block = Block::default()
+        .title("Choose an output device")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL);
+
+    f.render_widget(Clear, popout);
+    f.render_widget(block, popout);
+
+    let list = List::new(items)
+        .block(Block::default().title("List").borders(Borders::ALL))
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().add_modifier(Modifier::ITALIC).fg(Color::Green))
+        .highlight_symbol(">>");
+    let mut state = ListState::default();
+    state.select(Some(index));
+    f.render_stateful_widget(list, popout, &mut state);
+}
+
+/// Lists the audio output devices and switches the playback to the chosen one
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    mediainfo: &MediaInfo,
+    last_tick: &mut Instant,
+    tick_rate: Duration,
+) -> std::io::Result<()> {
+    let devices = audio_devices();
+
+    // the first entry goes back to `autoaudiosink`
+    let items: Vec<ListItem> = std::iter::once(ListItem::new("Default (autoaudiosink)"))
+        .chain(devices.iter().map(|it| ListItem::new(it.display_name().to_string())))
+        .collect();
+    let mut i = 0;
+
+    app.msgs.push(
+        "Press Enter to chose a device. Press jk to move up and down. Press Escape to cancel."
+            .into(),
+    ); // this message will not disappear
+    app.msgs.on_tick();
+
+    let index = loop {
+        terminal.draw(|f| render(f, app, mediainfo, &items, i))?;
+        let timeout =
+            tick_rate.checked_sub(last_tick.elapsed()).unwrap_or_else(|| Duration::from_secs(0));
+
+        if crossterm::event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => {
+                        app.msgs.push("Canceled choosing a device".into());
+                        break None;
+                    }
+
+                    KeyCode::Char('k') | KeyCode::Up => {
+                        i = i.saturating_sub(1);
+                    }
+
+                    KeyCode::Char('j') | KeyCode::Down => {
+                        i = std::cmp::min(i.saturating_add(1), items.len() - 1);
+                    }
+
+                    KeyCode::Enter => break Some(i),
+
+                    _ => continue,
+                }
+            }
+        }
+    };
+
+    match index {
+        Some(0) => actions::switch_audio_sink(app, mediainfo, None),
+        Some(i) => {
+            let sink = AudioSink::Device(devices[i - 1].clone());
+            actions::switch_audio_sink(app, mediainfo, Some(sink));
+        }
+        None => (),
+    }
+
+    Ok(())
+}
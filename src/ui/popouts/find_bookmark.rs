@@ -0,0 +1,202 @@
+use std::time::{Duration,
+                Instant};
+
+use crossterm::event::{self,
+                       Event,
+                       KeyCode};
+use tui::backend::Backend;
+use tui::layout::{Alignment,
+                  Constraint,
+                  Direction,
+                  Layout};
+use tui::style::{Color,
+                 Modifier,
+                 Style};
+use tui::widgets::{Block,
+                   Borders,
+                   Clear,
+                   List,
+                   ListItem,
+                   ListState,
+                   Paragraph};
+use tui::Terminal;
+
+use super::super::centered_rec_perc;
+use crate::data::mediainfo::MediaInfo;
+use crate::helpers::fuzzy_score;
+use crate::App;
+
+fn render<B: Backend>(
+    f: &mut tui::Frame<B>,
+    app: &mut App,
+    mediainfo: &MediaInfo,
+    input: &str,
+    items: &[ListItem],
+    index: usize,
+) {
+    super::super::render(f, app, mediainfo);
+
+    let popout = centered_rec_perc(75, 75, f.size());
+    let block = Block::default()
+        .title("Find a bookmark")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL);
+
+    f.render_widget(Clear, popout);
+    f.render_widget(block, popout);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(popout);
+
+    let paragraph = Paragraph::new(input)
+        .block(Block::default().title("Name").borders(Borders::ALL))
+        .style(Style::default().fg(Color::White));
+    f.render_widget(paragraph, chunks[0]);
+    f.set_cursor(chunks[0].x + input.chars().count() as u16 + 1, chunks[0].y + 1);
+
+    let list = List::new(items)
+        .block(Block::default().title("Matches").borders(Borders::ALL))
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().add_modifier(Modifier::ITALIC).fg(Color::Green))
+        .highlight_symbol(">>");
+    let mut state = ListState::default();
+    if !items.is_empty() {
+        state.select(Some(index));
+    }
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+/// Returns `(chapter index, bookmark index)` of bookmarks matching `input`, best matches first. In
+/// the antispoiler mode the chapters after the current one aren't searched
+fn find(mediainfo: &MediaInfo, input: &str, current_chapter_index: usize) -> Vec<(usize, usize)> {
+    let searched =
+        if mediainfo.is_antispoiler { current_chapter_index + 1 } else { mediainfo.chaptercount() };
+
+    let mut matches: Vec<_> = mediainfo.chapters[..searched]
+        .iter()
+        .enumerate()
+        .flat_map(|(chapter_index, chapter)| {
+            chapter.bookmarks.iter().enumerate().filter_map(move |(bk_index, bk)| {
+                fuzzy_score(input, &bk.name).map(|score| (score, chapter_index, bk_index))
+            })
+        })
+        .collect();
+
+    // stable, so equally good matches stay in the order of the book
+    matches.sort_by_key(|it| std::cmp::Reverse(it.0));
+    matches.into_iter().map(|(_, chapter_index, bk_index)| (chapter_index, bk_index)).collect()
+}
+
+/// Lets the user type a part of a bookmark name and jumps to the chosen match across all chapters
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    mediainfo: &mut MediaInfo,
+    last_tick: &mut Instant,
+    tick_rate: Duration,
+) -> std::io::Result<()> {
+    if mediainfo.chapters.iter().all(|it| it.bookmarks.is_empty()) {
+        app.msgs.push("None of the chapters have any bookmarks".into());
+        return Ok(());
+    }
+
+    let was_playing = app.player.is_playing_and_pause();
+    app.player.flush_position(app.get_mut_current_chapter(mediainfo));
+
+    app.msgs.push(
+        "Type to filter. Press Enter to jump to the bookmark. Press Up and Down to move. Press \
+         Escape to cancel."
+            .into(),
+    ); // this message will not disappear
+    app.msgs.on_tick();
+
+    let mut input = String::new();
+    let mut i = 0;
+
+    let selected = loop {
+        let matches = find(mediainfo, &input, app.current_chapter_index);
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|&(chapter_index, bk_index)| {
+                let chapter = &mediainfo.chapters[chapter_index];
+                let bk = &chapter.bookmarks[bk_index];
+                ListItem::new(format!(
+                    "{} | {} | chapter name: {}",
+                    bk.name,
+                    bk.display(chapter.start_position, app.relative_positions),
+                    chapter.get_title_or_filename()
+                ))
+            })
+            .collect();
+
+        terminal.draw(|f| render(f, app, mediainfo, &input, &items, i))?;
+        let timeout =
+            tick_rate.checked_sub(last_tick.elapsed()).unwrap_or_else(|| Duration::from_secs(0));
+
+        if crossterm::event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    KeyCode::Esc => {
+                        app.msgs.push("Canceled finding a bookmark".into());
+                        break None;
+                    }
+
+                    KeyCode::Up => {
+                        i = i.saturating_sub(1);
+                    }
+
+                    KeyCode::Down => {
+                        i = std::cmp::min(i + 1, matches.len().saturating_sub(1));
+                    }
+
+                    KeyCode::Enter => {
+                        if let Some(&selected) = matches.get(i) {
+                            break Some(selected);
+                        }
+                    }
+
+                    KeyCode::Backspace => {
+                        input.pop();
+                        i = 0;
+                    }
+
+                    KeyCode::Char(c) => {
+                        input.push(c);
+                        i = 0;
+                    }
+
+                    _ => continue,
+                }
+            }
+        }
+    };
+
+    if let Some((chapter_index, bk_index)) = selected {
+        let curent_pos = match app.player.get_position_sec() {
+            Some(pos) => pos,
+            None => {
+                app.msgs.push("Couldn't get the position".into());
+                if was_playing {
+                    app.player.play();
+                }
+                return Ok(());
+            }
+        };
+        if chapter_index == app.current_chapter_index {
+            app.pos_and_chap_before_jump = Some((curent_pos, chapter_index));
+            app.get_mut_current_chapter(mediainfo).before_jump_position = Some(curent_pos);
+        } else {
+            app.pos_and_chap_before_jump = Some((curent_pos, app.current_chapter_index));
+        }
+        app.bookmark_select(Some(chapter_index), bk_index, mediainfo);
+    }
+
+    if was_playing {
+        app.player.play();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use std::time::{Duration,
+                Instant};
+
+use crossterm::event::{self,
+                       Event,
+                       KeyCode};
+use tui::backend::Backend;
+use tui::layout::Alignment;
+use tui::style::{Color,
+                 Modifier,
+                 Style};
+use tui::widgets::{Block,
+                   Borders,
+                   Clear,
+                   List,
+                   ListItem,
+                   ListState};
+use tui::Terminal;
+
+use super::super::centered_rec_perc;
+use crate::data::mediainfo::MediaInfo;
+use crate::App;
+
+fn render<B: Backend>(
+    f: &mut tui::Frame<B>,
+    app: &mut App,
+    mediainfo: &MediaInfo,
+    items: &[ListItem],
+    index: usize,
+) {
+    super::super::render(f, app, mediainfo);
+
+    let popout = centered_rec_perc(75, 75, f.size());
+    let block = Block::default()
+        .title("Choose a chapter")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL);
+
+    f.render_widget(Clear, popout);
+    f.render_widget(block, popout);
+
+    let list = List::new(items)
+        .block(Block::default().title("Matches").borders(Borders::ALL))
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().add_modifier(Modifier::ITALIC).fg(Color::Green))
+        .highlight_symbol(">>");
+    let mut state = ListState::default();
+    state.select(Some(index));
+    f.render_stateful_widget(list, popout, &mut state);
+}
+
+/// Returns indexes of chapters whose title or description contains `query`, ignoring case. In the
+/// antispoiler mode chapters after the current one aren't searched
+fn find(mediainfo: &MediaInfo, query: &str, current_chapter_index: usize) -> Vec<usize> {
+    let query = query.to_lowercase();
+    let searched =
+        if mediainfo.is_antispoiler { current_chapter_index + 1 } else { mediainfo.chaptercount() };
+
+    mediainfo.chapters[..searched]
+        .iter()
+        .enumerate()
+        .filter(|(_, chapter)| {
+            chapter.get_title_or_filename().to_lowercase().contains(&query)
+                || chapter.description.iter().any(|it| it.to_lowercase().contains(&query))
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Asks for a part of a chapter title or description and loads the chosen match
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    mediainfo: &mut MediaInfo,
+    last_tick: &mut Instant,
+    tick_rate: Duration,
+) -> std::io::Result<()> {
+    let query = match super::input::run(
+        terminal,
+        app,
+        mediainfo,
+        last_tick,
+        tick_rate,
+        "Input a part of the chapter title or description",
+        None,
+    )? {
+        Some(query) => query,
+        None => {
+            app.msgs.push("Canceled finding a chapter".into());
+            return Ok(());
+        }
+    };
+
+    let matches = find(mediainfo, query.trim(), app.current_chapter_index);
+    if matches.is_empty() {
+        app.msgs.push("No matches".into());
+        return Ok(());
+    }
+
+    app.msgs.push(
+        "Press Enter to chose a chapter. Press jk to move up and down. Press Escape to cancel."
+            .into(),
+    ); // this message will not disappear
+    app.msgs.on_tick();
+
+    let items: Vec<ListItem> = matches
+        .iter()
+        .map(|&index| {
+            let chapter = &mediainfo.chapters[index];
+            match &chapter.description {
+                Some(desc) => ListItem::new(format!(
+                    "{} | {} [{}]",
+                    index + 1,
+                    chapter.get_title_or_filename(),
+                    desc
+                )),
+                None => {
+                    ListItem::new(format!("{} | {}", index + 1, chapter.get_title_or_filename()))
+                }
+            }
+        })
+        .collect();
+
+    let mut i = 0;
+    let selected = loop {
+        terminal.draw(|f| render(f, app, mediainfo, &items, i))?;
+        let timeout =
+            tick_rate.checked_sub(last_tick.elapsed()).unwrap_or_else(|| Duration::from_secs(0));
+
+        if crossterm::event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => {
+                        app.msgs.push("Canceled finding a chapter".into());
+                        break None;
+                    }
+
+                    KeyCode::Char('k') | KeyCode::Up => {
+                        i = i.saturating_sub(1);
+                    }
+
+                    KeyCode::Char('j') | KeyCode::Down => {
+                        i = std::cmp::min(i + 1, matches.len() - 1);
+                    }
+
+                    KeyCode::Enter => break Some(matches[i]),
+
+                    _ => continue,
+                }
+            }
+        }
+    };
+
+    if let Some(index) = selected {
+        let pos = match app.player.get_position_sec() {
+            Some(pos) => pos,
+            None => {
+                app.msgs.push("Couldn't get the position".into());
+                return Ok(());
+            }
+        };
+        app.get_mut_current_chapter(mediainfo).update_last_position(pos);
+        app.pos_and_chap_before_jump = Some((pos, app.current_chapter_index));
+
+        let was_playing = app.player.is_playing_and_pause();
+        app.load_chapter(index, mediainfo);
+        if was_playing {
+            app.player.play();
+        }
+        mediainfo.marked_position = None;
+        app.msgs.push(format!("Moved to chapter {}", index + 1).into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::io;
+use std::time::{Duration,
+                Instant};
+
+use crossterm::event::{self,
+                       Event,
+                       KeyCode};
+use tui::backend::Backend;
+use tui::layout::Alignment;
+use tui::style::{Color,
+                 Style};
+use tui::widgets::{Block,
+                   Borders,
+                   Clear,
+                   Paragraph,
+                   Wrap};
+use tui::Terminal;
+
+use super::super::centered_rec_perc;
+use crate::data::mediainfo::MediaInfo;
+use crate::App;
+
+fn render<B: Backend>(
+    f: &mut tui::Frame<B>,
+    app: &mut App,
+    mediainfo: &MediaInfo,
+    title: &str,
+    text: &str,
+) {
+    super::super::render(f, app, mediainfo);
+
+    let popout = centered_rec_perc(50, 50, f.size());
+    let block = Block::default()
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, popout); // this clears out the background
+    f.render_widget(paragraph, popout);
+}
+
+/// Shows read-only `text` in a popout until the user closes it
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    mediainfo: &MediaInfo,
+    last_tick: &mut Instant,
+    tick_rate: Duration,
+    title: &str,
+    text: &str,
+) -> io::Result<()> {
+    loop {
+        terminal.draw(|f| render(f, app, mediainfo, title, text))?;
+        let timeout =
+            tick_rate.checked_sub(last_tick.elapsed()).unwrap_or_else(|| Duration::from_secs(0));
+
+        if crossterm::event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => break,
+                    _ => continue,
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -7,7 +7,8 @@ use crossterm::event::{self,
                        KeyCode,
                        KeyModifiers};
 use tui::backend::Backend;
-use tui::layout::Alignment;
+use tui::layout::{Alignment,
+                  Rect};
 use tui::style::{Color,
                  Style};
 use tui::widgets::{Block,
@@ -19,13 +20,16 @@ use crate::data::mediainfo::MediaInfo;
 use crate::ui::centered_rect_flat;
 use crate::App;
 
+/// How many entries are remembered for each prompt
+const HISTORY_LEN: usize = 20;
+
 fn render<B: Backend>(
     f: &mut tui::Frame<B>,
     app: &mut App,
     mediainfo: &MediaInfo,
     prompt: &'static str,
     input: &String,
-    width: u16,
+    preview: Option<&dyn Fn(&str) -> String>,
 ) {
     super::super::render(f, app, mediainfo);
     let block = Block::default()
@@ -34,18 +38,61 @@ fn render<B: Backend>(
         .borders(tui::widgets::Borders::ALL)
         .border_style(Style::default().fg(Color::White));
 
+    let input_len = input.chars().count();
+    let width = popout_width(prompt.chars().count().max(input_len + 1), f.size().width);
+
+    // keep the end of the input and the cursor visible if it doesn't fit
+    let scroll = (input_len + 1).saturating_sub(width.into()) as u16;
+
     let paragraph = Paragraph::new(input.as_str())
         .block(block)
-        .style(Style::default().fg(Color::White).bg(Color::Black));
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .scroll((0, scroll));
 
     if let Some(area) = centered_rect_flat(width, 3, f.size()) {
         f.render_widget(Clear, area); //this clears out the background
         f.render_widget(paragraph, area);
-        f.set_cursor(input.len() as u16 + area.x + 1, area.y + 1);
+        f.set_cursor(input_len as u16 - scroll + area.x + 1, area.y + 1);
+
+        if let Some(preview) = preview {
+            let preview_area = Rect { y: area.y + area.height, height: 1, ..area };
+            if preview_area.bottom() <= f.size().bottom() {
+                let paragraph = Paragraph::new(preview(input))
+                    .alignment(Alignment::Center)
+                    .style(Style::default().fg(Color::White).bg(Color::Black));
+                f.render_widget(Clear, preview_area);
+                f.render_widget(paragraph, preview_area);
+            }
+        }
     };
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Adds `input` to the history of the `prompt`
+fn remember(app: &mut App, prompt: &'static str, input: &str) {
+    if input.is_empty() {
+        return;
+    }
+
+    let history = app.input_history.entry(prompt).or_default();
+    if history.last().map(String::as_str) != Some(input) {
+        history.push(input.to_owned());
+    }
+    if history.len() > HISTORY_LEN {
+        history.remove(0);
+    }
+}
+
+/// Computes the width of the popout so `content_len` characters fit inside it, clamped between
+/// `MIN_WIDTH` and what fits into `terminal_width`
+fn popout_width(content_len: usize, terminal_width: u16) -> u16 {
+    const MIN_WIDTH: u16 = 30;
+
+    // `centered_rect_flat` needs 4 columns of the terminal to spare
+    let max_width = terminal_width.saturating_sub(4);
+    let width = u16::try_from(content_len).unwrap_or(u16::MAX).max(MIN_WIDTH);
+    width.min(max_width)
+}
+
 pub fn run<B: Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
@@ -54,16 +101,37 @@ pub fn run<B: Backend>(
     tick_rate: Duration,
     prompt: &'static str,
     input: Option<&String>,
-    width: u16,
+) -> io::Result<Option<String>> {
+    run_with_preview(terminal, app, mediainfo, last_tick, tick_rate, prompt, input, None)
+}
+
+/// Same as `run` but shows the result of `preview` called with the current input under the
+/// input box
+#[allow(clippy::too_many_arguments)]
+pub fn run_with_preview<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    mediainfo: &MediaInfo,
+    last_tick: &mut Instant,
+    tick_rate: Duration,
+    prompt: &'static str,
+    input: Option<&String>,
+    preview: Option<&dyn Fn(&str) -> String>,
 ) -> io::Result<Option<String>> {
     let mut input = if let Some(x) = input { x.clone() } else { String::new() };
 
+    let history = app.input_history.get(prompt).cloned().unwrap_or_default();
+    // index into `history` of the recalled entry, `None` while editing a new one
+    let mut history_index: Option<usize> = None;
+    // what was typed before going through the history
+    let mut draft = String::new();
+
     // This will show the pushed message for the whole duration for the following loop
     app.msgs.push("Press Escape to cancel".into());
     app.msgs.on_tick();
 
     loop {
-        terminal.draw(|f| render(f, app, mediainfo, prompt, &input, width))?;
+        terminal.draw(|f| render(f, app, mediainfo, prompt, &input, preview))?;
         let timeout =
             tick_rate.checked_sub(last_tick.elapsed()).unwrap_or_else(|| Duration::from_secs(0));
 
@@ -71,8 +139,37 @@ pub fn run<B: Backend>(
             if let Event::Key(key) = event::read()? {
                 match key.code {
                     KeyCode::Enter => {
+                        remember(app, prompt, &input);
                         break Ok(Some(input.drain(..).collect::<String>()));
                     }
+                    KeyCode::Up => {
+                        let index = match history_index {
+                            Some(0) => continue,
+                            Some(i) => i - 1,
+                            None if history.is_empty() => continue,
+                            None => {
+                                draft = input.clone();
+                                history.len() - 1
+                            }
+                        };
+                        history_index = Some(index);
+                        input = history[index].clone();
+                        continue;
+                    }
+                    KeyCode::Down => {
+                        match history_index {
+                            Some(i) if i + 1 < history.len() => {
+                                history_index = Some(i + 1);
+                                input = history[i + 1].clone();
+                            }
+                            Some(_) => {
+                                history_index = None;
+                                input = std::mem::take(&mut draft);
+                            }
+                            None => (),
+                        }
+                        continue;
+                    }
                     KeyCode::Char(c) => {
                         if key.modifiers == KeyModifiers::CONTROL && c == 'w' {
                             while let Some(pop) = input.pop() {
@@ -99,3 +196,16 @@ pub fn run<B: Backend>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_fits_content() {
+        assert_eq!(popout_width(50, 200), 50);
+        assert_eq!(popout_width(5, 200), 30);
+        assert_eq!(popout_width(150, 80), 76);
+        assert_eq!(popout_width(10, 2), 0);
+    }
+}
@@ -1,5 +1,9 @@
 pub mod all_bookmarks;
 pub mod bookmarks;
+pub mod devices;
+pub mod find_bookmark;
+pub mod find_chapter;
 pub mod help_menu;
+pub mod info;
 pub mod input;
 pub mod yn;
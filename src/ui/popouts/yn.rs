@@ -21,7 +21,7 @@ fn render<'a, B: Backend>(
     f: &mut tui::Frame<B>,
     app: &mut App,
     mediainfo: &'a MediaInfo,
-    prompt: &'static str,
+    prompt: &str,
 ) {
     super::super::render(f, app, mediainfo);
 
@@ -48,7 +48,7 @@ pub fn run<B: Backend>(
     mediainfo: &MediaInfo,
     last_tick: &mut Instant,
     tick_rate: Duration,
-    prompt: &'static str,
+    prompt: &str,
 ) -> std::io::Result<bool> {
     loop {
         terminal.draw(|f| render(f, app, mediainfo, prompt))?;